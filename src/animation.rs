@@ -0,0 +1,255 @@
+use anyhow::{Context, bail};
+use quad::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    ty::BoxedFuture,
+};
+
+use crate::{
+    key_value,
+    player::{Player, PlayerOrientation, PlayerState},
+};
+
+pub const PLAYER_ANIMATION_PATH: &str = "player.anim";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ClipName {
+    Idle,
+    Walk,
+    Jump,
+    Fall,
+    Squash,
+    Death,
+}
+
+impl ClipName {
+    pub const ALL: [ClipName; 6] = [
+        ClipName::Idle,
+        ClipName::Walk,
+        ClipName::Jump,
+        ClipName::Fall,
+        ClipName::Squash,
+        ClipName::Death,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            ClipName::Idle => "idle",
+            ClipName::Walk => "walk",
+            ClipName::Jump => "jump",
+            ClipName::Fall => "fall",
+            ClipName::Squash => "squash",
+            ClipName::Death => "death",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|clip| clip.key() == text)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Clip {
+    pub first: usize,
+    pub last: usize,
+    /// Frames per second, zero advances the clip with the distance walked instead of time.
+    pub fps: f32,
+    pub flip: bool,
+}
+
+impl Clip {
+    fn parse(value: &str) -> anyhow::Result<Self> {
+        let mut parts = value.split(',').map(str::trim);
+        let frames = parts.next().unwrap_or_default();
+        let (first, last) = match frames.split_once('-') {
+            Some((first, last)) => (first.trim().parse()?, last.trim().parse()?),
+            None => {
+                let frame = frames.parse()?;
+                (frame, frame)
+            }
+        };
+        if last < first {
+            bail!("Invalid frame range: {}", frames);
+        }
+        let fps = match parts.next() {
+            Some(fps) => fps.parse()?,
+            None => 0.0,
+        };
+        let flip = match parts.next() {
+            Some("flip") => true,
+            Some(flag) => bail!("Unknown clip flag: {}", flag),
+            None => false,
+        };
+        Ok(Self {
+            first,
+            last,
+            fps,
+            flip,
+        })
+    }
+
+    fn flipped(self) -> Self {
+        Self {
+            flip: !self.flip,
+            ..self
+        }
+    }
+
+    /// Frame shown after `time` seconds or `phase` frames walked, looping over the range.
+    pub fn frame(&self, time: f32, phase: f32) -> usize {
+        let step = if self.fps > 0.0 {
+            time * self.fps
+        } else {
+            phase
+        };
+        self.first + step as usize % (self.last - self.first + 1)
+    }
+}
+
+/// Sprite sheet layout and animation clips of the player, loaded from a `.anim` file.
+pub struct PlayerAnimation {
+    pub image: String,
+    pub frame_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    clips: Vec<(ClipName, Clip, Clip)>,
+}
+
+impl PlayerAnimation {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let text = std::str::from_utf8(bytes)?;
+        let mut image = None;
+        let mut frame_size = None;
+        let mut grid = None;
+        let mut right = Vec::new();
+        let mut left = Vec::new();
+        for (key, value) in key_value::parse(text)? {
+            match key.split('.').collect::<Vec<_>>()[..] {
+                ["image"] => image = Some(value.to_string()),
+                ["frame"] => match key_value::parse_list::<f32>(value)?[..] {
+                    [width, height] => frame_size = Some(Vec2::new(width, height)),
+                    _ => bail!("Invalid frame size: {}", value),
+                },
+                ["grid"] => match key_value::parse_list::<usize>(value)?[..] {
+                    [columns, rows] => grid = Some((columns, rows)),
+                    _ => bail!("Invalid grid: {}", value),
+                },
+                ["clip", name, orientation] => {
+                    let Some(name) = ClipName::parse(name) else {
+                        bail!("Unknown clip: {}", name);
+                    };
+                    let clip = Clip::parse(value).with_context(|| format!("Clip {}", key))?;
+                    match orientation {
+                        "right" => right.push((name, clip)),
+                        "left" => left.push((name, clip)),
+                        _ => bail!("Invalid orientation: {}", orientation),
+                    }
+                }
+                _ => bail!("Unknown animation property: {}", key),
+            }
+        }
+
+        let (columns, rows) = grid.context("Missing grid")?;
+        let mut clips = Vec::new();
+        for name in ClipName::ALL {
+            let find = |clips: &[(ClipName, Clip)]| {
+                clips
+                    .iter()
+                    .find(|(clip_name, _)| *clip_name == name)
+                    .map(|(_, clip)| *clip)
+            };
+            // A missing left clip mirrors the right one
+            let Some(right_clip) = find(&right) else {
+                bail!("Missing clip: {}", name.key());
+            };
+            let left_clip = find(&left).unwrap_or_else(|| right_clip.flipped());
+            if right_clip.last.max(left_clip.last) >= columns * rows {
+                bail!("Clip {} is outside of the sprite sheet", name.key());
+            }
+            clips.push((name, right_clip, left_clip));
+        }
+
+        Ok(Self {
+            image: image.context("Missing image")?,
+            frame_size: frame_size.context("Missing frame size")?,
+            columns,
+            rows,
+            clips,
+        })
+    }
+
+    /// Atlas cutting the given copy of the sprite sheet into frames.
+    pub fn atlas(&self, texture: Handle<Image>) -> TextureAtlas {
+        TextureAtlas::from_grid(texture, self.frame_size, self.columns, self.rows)
+    }
+
+    pub fn clip(&self, name: ClipName, orientation: PlayerOrientation) -> &Clip {
+        let (_, right, left) = self
+            .clips
+            .iter()
+            .find(|(clip_name, _, _)| *clip_name == name)
+            .unwrap();
+        match orientation {
+            PlayerOrientation::Left => left,
+            PlayerOrientation::Right => right,
+        }
+    }
+
+    /// Sprite index and horizontal flip for the current state of the player.
+    pub fn sprite(&self, player: &Player, walking: bool) -> (usize, bool) {
+        let name = match player.state {
+            PlayerState::Standing if walking => ClipName::Walk,
+            PlayerState::Standing => ClipName::Idle,
+            PlayerState::Jumping => ClipName::Jump,
+            PlayerState::Falling => ClipName::Fall,
+            PlayerState::Squashed => ClipName::Squash,
+            PlayerState::Dying => ClipName::Death,
+        };
+        let clip = self.clip(name, player.orientation);
+        let frame = clip.frame(player.animation_time, player.animation_phase);
+        (frame, clip.flip)
+    }
+}
+
+#[derive(Default)]
+pub struct PlayerAnimationLoader;
+
+impl AssetLoader for PlayerAnimationLoader {
+    fn load(
+        &self,
+        bytes: &[u8],
+        load_context: &mut LoadContext,
+    ) -> BoxedFuture<'_, anyhow::Result<()>> {
+        let result = PlayerAnimation::from_bytes(bytes)
+            .map(|animation| load_context.set_default_asset(LoadedAsset::new(animation)));
+        Box::pin(async move { result })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constant::DEATH_ANIMATION_TIME, mouse::asset_path};
+
+    #[test]
+    fn death_clip_plays_once_within_the_sequence() {
+        let bytes = std::fs::read(asset_path(PLAYER_ANIMATION_PATH)).unwrap();
+        let animation = PlayerAnimation::from_bytes(&bytes).unwrap();
+        for orientation in [PlayerOrientation::Left, PlayerOrientation::Right] {
+            let clip = animation.clip(ClipName::Death, orientation);
+            let frames: Vec<usize> = (0..=15)
+                .map(|step| clip.frame(DEATH_ANIMATION_TIME * step as f32 / 15.0, 0.0))
+                .collect();
+            assert!(
+                frames.windows(2).all(|pair| pair[0] <= pair[1]),
+                "{frames:?}"
+            );
+            assert_eq!(frames[0], clip.first);
+            assert_eq!(frames[15], clip.last);
+        }
+    }
+}
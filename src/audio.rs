@@ -0,0 +1,331 @@
+use quad::prelude::*;
+
+use crate::{
+    config::{Config, MAX_VOLUME},
+    input::{Action, ActionState},
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Sound {
+    Jump,
+    Land,
+    Death,
+    Exit,
+    Select,
+}
+
+impl Sound {
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Jump => "sounds/jump.wav",
+            Self::Land => "sounds/land.wav",
+            Self::Death => "sounds/death.wav",
+            Self::Exit => "sounds/exit.wav",
+            Self::Select => "sounds/select.wav",
+        }
+    }
+}
+
+/// Output device, volumes are in the range 0 to 1.
+pub trait AudioBackend: Send + Sync {
+    fn play_sound(&mut self, sound: Sound, volume: f32);
+    /// Loops the music, a path relative to the assets, until it is replaced or stopped.
+    fn play_music(&mut self, path: &str, volume: f32);
+    fn stop_music(&mut self);
+    fn set_music_volume(&mut self, volume: f32);
+}
+
+/// Backend for machines without an audio device, everything is silently dropped.
+pub struct NullBackend;
+
+impl AudioBackend for NullBackend {
+    fn play_sound(&mut self, sound: Sound, _volume: f32) {
+        log::trace!("Dropped sound {}", sound.path());
+    }
+
+    fn play_music(&mut self, _path: &str, _volume: f32) {}
+
+    fn stop_music(&mut self) {}
+
+    fn set_music_volume(&mut self, _volume: f32) {}
+}
+
+#[derive(Resource)]
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    music: Option<String>,
+    sound_volume: f32,
+    music_volume: f32,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>, config: &Config) -> Self {
+        let mut audio = Self {
+            backend,
+            music: None,
+            sound_volume: 0.0,
+            music_volume: 0.0,
+        };
+        audio.set_volume(config);
+        audio
+    }
+
+    /// Plays on the default output device, silently when there is none or the default
+    /// `audio` feature is disabled.
+    pub fn from_device(config: &Config) -> Self {
+        #[cfg(feature = "audio")]
+        match rodio_backend::RodioBackend::new() {
+            Ok(backend) => return Self::new(Box::new(backend), config),
+            Err(error) => log::warn!("Audio disabled: {error}"),
+        }
+        Self::new(Box::new(NullBackend), config)
+    }
+
+    pub fn set_volume(&mut self, config: &Config) {
+        self.sound_volume = config.sound_volume as f32 / MAX_VOLUME as f32;
+        self.music_volume = config.music_volume as f32 / MAX_VOLUME as f32;
+        self.backend.set_music_volume(self.music_volume);
+    }
+
+    pub fn play(&mut self, sound: Sound) {
+        if self.sound_volume > 0.0 {
+            self.backend.play_sound(sound, self.sound_volume);
+        }
+    }
+
+    /// Keeps playing if the music is already on, `None` stops it.
+    pub fn play_music(&mut self, path: Option<&str>) {
+        if self.music.as_deref() == path {
+            return;
+        }
+        self.music = path.map(str::to_string);
+        match path {
+            Some(path) => self.backend.play_music(path, self.music_volume),
+            None => self.backend.stop_music(),
+        }
+    }
+}
+
+/// Click of the menu cursor, to be chained after `update_actions` in menu scenes.
+pub fn play_menu_sounds(actions: Res<ActionState>, mut audio: ResMut<Audio>) {
+    play_menu_sound(&mut audio, &actions);
+}
+
+fn play_menu_sound(audio: &mut Audio, actions: &ActionState) {
+    if [Action::Up, Action::Down, Action::Confirm]
+        .into_iter()
+        .any(|action| actions.just_pressed(action))
+    {
+        audio.play(Sound::Select);
+    }
+}
+
+/// Backend remembering everything it was asked to play, shared with the test which created it.
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct RecordingBackend {
+    sounds: std::sync::Arc<std::sync::Mutex<Vec<Sound>>>,
+    music: std::sync::Arc<std::sync::Mutex<Vec<Option<String>>>>,
+}
+
+#[cfg(test)]
+impl RecordingBackend {
+    /// Audio with the default volumes playing into the returned recorder.
+    pub fn audio() -> (Audio, Self) {
+        let recorder = Self::default();
+        let audio = Audio::new(Box::new(recorder.clone()), &Config::default());
+        (audio, recorder)
+    }
+
+    pub fn sounds(&self) -> Vec<Sound> {
+        self.sounds.lock().unwrap().clone()
+    }
+
+    pub fn music(&self) -> Vec<Option<String>> {
+        self.music.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl AudioBackend for RecordingBackend {
+    fn play_sound(&mut self, sound: Sound, _volume: f32) {
+        self.sounds.lock().unwrap().push(sound);
+    }
+
+    fn play_music(&mut self, path: &str, _volume: f32) {
+        self.music.lock().unwrap().push(Some(path.to_string()));
+    }
+
+    fn stop_music(&mut self) {
+        self.music.lock().unwrap().push(None);
+    }
+
+    fn set_music_volume(&mut self, _volume: f32) {}
+}
+
+#[cfg(feature = "audio")]
+mod rodio_backend {
+    use std::{
+        fs::File,
+        io::BufReader,
+        path::Path,
+        sync::mpsc::{self, Receiver, Sender},
+        thread,
+    };
+
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+
+    use super::{AudioBackend, Sound};
+
+    enum Command {
+        Sound(Sound, f32),
+        Music(String, f32),
+        StopMusic,
+        MusicVolume(f32),
+    }
+
+    /// The output stream can't leave the thread it was created on, so it lives on its own thread.
+    pub struct RodioBackend {
+        sender: Sender<Command>,
+    }
+
+    impl RodioBackend {
+        pub fn new() -> anyhow::Result<Self> {
+            let (sender, receiver) = mpsc::channel();
+            let (ready_sender, ready) = mpsc::channel();
+            thread::spawn(move || match OutputStream::try_default() {
+                Ok((_stream, handle)) => {
+                    let _ = ready_sender.send(Ok(()));
+                    run(&handle, receiver);
+                }
+                Err(error) => {
+                    let _ = ready_sender.send(Err(error));
+                }
+            });
+            ready.recv()??;
+            Ok(Self { sender })
+        }
+
+        fn send(&self, command: Command) {
+            let _ = self.sender.send(command);
+        }
+    }
+
+    const ASSET_DIR: &str = "assets";
+
+    fn decode(path: &str) -> anyhow::Result<Decoder<BufReader<File>>> {
+        let file = File::open(Path::new(ASSET_DIR).join(path))?;
+        Ok(Decoder::new(BufReader::new(file))?)
+    }
+
+    fn run(handle: &OutputStreamHandle, receiver: Receiver<Command>) {
+        let mut music: Option<Sink> = None;
+        for command in receiver {
+            let result = match command {
+                Command::Sound(sound, volume) => decode(sound.path()).and_then(|source| {
+                    let sink = Sink::try_new(handle)?;
+                    sink.set_volume(volume);
+                    sink.append(source);
+                    sink.detach();
+                    Ok(())
+                }),
+                Command::Music(path, volume) => decode(&path).and_then(|source| {
+                    let sink = Sink::try_new(handle)?;
+                    sink.set_volume(volume);
+                    sink.append(source.repeat_infinite());
+                    music = Some(sink);
+                    Ok(())
+                }),
+                Command::StopMusic => {
+                    music = None;
+                    Ok(())
+                }
+                Command::MusicVolume(volume) => {
+                    if let Some(sink) = &music {
+                        sink.set_volume(volume);
+                    }
+                    Ok(())
+                }
+            };
+            if let Err(error) = result {
+                log::warn!("Audio error: {error}");
+            }
+        }
+    }
+
+    impl AudioBackend for RodioBackend {
+        fn play_sound(&mut self, sound: Sound, volume: f32) {
+            self.send(Command::Sound(sound, volume));
+        }
+
+        fn play_music(&mut self, path: &str, volume: f32) {
+            self.send(Command::Music(path.to_string(), volume));
+        }
+
+        fn stop_music(&mut self) {
+            self.send(Command::StopMusic);
+        }
+
+        fn set_music_volume(&mut self, volume: f32) {
+            self.send(Command::MusicVolume(volume));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{input::actions, mouse::asset_path};
+
+    #[test]
+    fn sound_files_exist() {
+        for sound in [
+            Sound::Jump,
+            Sound::Land,
+            Sound::Death,
+            Sound::Exit,
+            Sound::Select,
+        ] {
+            assert!(asset_path(sound.path()).is_file(), "{}", sound.path());
+        }
+    }
+
+    #[test]
+    fn menu_selection_clicks() {
+        for action in [Action::Up, Action::Down, Action::Confirm] {
+            let (mut audio, recorder) = RecordingBackend::audio();
+            play_menu_sound(&mut audio, &actions(&[action]));
+            assert_eq!(recorder.sounds(), [Sound::Select]);
+        }
+    }
+
+    #[test]
+    fn menu_without_selection_is_silent() {
+        let (mut audio, recorder) = RecordingBackend::audio();
+        play_menu_sound(&mut audio, &actions(&[Action::Left, Action::Back]));
+        assert!(recorder.sounds().is_empty());
+    }
+
+    #[test]
+    fn muted_sounds_are_not_played() {
+        let (mut audio, recorder) = RecordingBackend::audio();
+        audio.set_volume(&Config {
+            sound_volume: 0,
+            ..Default::default()
+        });
+        audio.play(Sound::Jump);
+        assert!(recorder.sounds().is_empty());
+    }
+
+    #[test]
+    fn music_restarts_only_when_changed() {
+        let (mut audio, recorder) = RecordingBackend::audio();
+        audio.play_music(Some("music/level1.wav"));
+        audio.play_music(Some("music/level1.wav"));
+        audio.play_music(None);
+        assert_eq!(
+            recorder.music(),
+            [Some("music/level1.wav".to_string()), None]
+        );
+    }
+}
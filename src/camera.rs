@@ -0,0 +1,222 @@
+use quad::prelude::*;
+
+use crate::constant::*;
+
+struct Shake {
+    amplitude: f32,
+    duration: f32,
+    time: f32,
+}
+
+struct Pan {
+    target: f32,
+    /// Time to stay on the target once it is reached.
+    hold: f32,
+}
+
+/// Horizontal camera following the player, positions are in level pixels.
+/// The state only changes through `update`, so the same inputs always give the same path.
+pub struct CameraRig {
+    /// Width of the area around the focus the player can move in without moving the camera.
+    pub dead_zone: f32,
+    /// Distance the camera leads the player in the facing direction.
+    pub look_ahead: f32,
+    /// Approximate time to catch up with the target.
+    pub smooth_time: f32,
+    position: f32,
+    velocity: f32,
+    focus: f32,
+    shake: Option<Shake>,
+    pan: Option<Pan>,
+}
+
+impl CameraRig {
+    /// Camera already settled on the player.
+    pub fn new(player_x: f32, facing: f32, min: f32, max: f32) -> Self {
+        let mut rig = Self {
+            dead_zone: CAMERA_DEAD_ZONE,
+            look_ahead: CAMERA_LOOK_AHEAD,
+            smooth_time: CAMERA_SMOOTH_TIME,
+            position: 0.0,
+            velocity: 0.0,
+            focus: player_x,
+            shake: None,
+            pan: None,
+        };
+        rig.position = rig.target(facing, min, max);
+        rig
+    }
+
+    pub fn position(&self) -> f32 {
+        self.position
+    }
+
+    pub fn shake(&mut self, amplitude: f32, duration: f32) {
+        self.shake = Some(Shake {
+            amplitude,
+            duration,
+            time: 0.0,
+        });
+    }
+
+    /// Moves to `target`, stays there for `hold` seconds and returns to the player.
+    pub fn pan_to(&mut self, target: f32, hold: f32) {
+        self.pan = Some(Pan { target, hold });
+    }
+
+    /// `facing` is -1 when the player looks left and 1 when right.
+    pub fn update(&mut self, player_x: f32, facing: f32, dt: f32, min: f32, max: f32) -> f32 {
+        let half_zone = self.dead_zone / 2.0;
+        self.focus = self.focus.clamp(player_x - half_zone, player_x + half_zone);
+
+        let target = match &mut self.pan {
+            Some(pan) => {
+                let target = pan.target.clamp(min, max);
+                if (self.position - target).abs() < 1.0 {
+                    pan.hold -= dt;
+                }
+                target
+            }
+            None => self.target(facing, min, max),
+        };
+        if self.pan.as_ref().is_some_and(|pan| pan.hold <= 0.0) {
+            self.pan = None;
+        }
+
+        self.position = smooth_damp(
+            self.position,
+            target,
+            &mut self.velocity,
+            self.smooth_time,
+            dt,
+        )
+        .clamp(min, max);
+
+        if let Some(shake) = &mut self.shake {
+            shake.time += dt;
+            if shake.time >= shake.duration {
+                self.shake = None;
+            }
+        }
+        self.position
+    }
+
+    /// Offset added to the camera position, fades out over the shake duration.
+    pub fn shake_offset(&self) -> Vec2 {
+        match &self.shake {
+            Some(shake) => {
+                let strength = shake.amplitude * (1.0 - shake.time / shake.duration);
+                let phase = shake.time * CAMERA_SHAKE_FREQUENCY;
+                Vec2::new(phase.sin(), (phase * 1.3).cos()) * strength
+            }
+            None => Vec2::ZERO,
+        }
+    }
+
+    fn target(&self, facing: f32, min: f32, max: f32) -> f32 {
+        (self.focus + facing * self.look_ahead).clamp(min, max)
+    }
+}
+
+/// Critically damped spring towards `target`, stable for any time step.
+fn smooth_damp(current: f32, target: f32, velocity: &mut f32, smooth_time: f32, dt: f32) -> f32 {
+    let omega = 2.0 / smooth_time.max(f32::EPSILON);
+    let x = omega * dt;
+    let decay = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * decay;
+    target + (change + temp) * decay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+    const MIN: f32 = 0.0;
+    const MAX: f32 = 10_000.0;
+
+    fn settled(rig: &mut CameraRig, player_x: f32, facing: f32) -> f32 {
+        for _ in 0..600 {
+            rig.update(player_x, facing, DT, MIN, MAX);
+        }
+        rig.position()
+    }
+
+    #[test]
+    fn camera_holds_still_inside_dead_zone() {
+        let mut rig = CameraRig::new(1000.0, 1.0, MIN, MAX);
+        let start = rig.position();
+        let half_zone = rig.dead_zone / 2.0;
+        for step in 0..120 {
+            let offset = (step as f32 * 0.1).sin() * half_zone;
+            assert_eq!(rig.update(1000.0 + offset, 1.0, DT, MIN, MAX), start);
+        }
+    }
+
+    #[test]
+    fn smoothing_converges_without_overshoot() {
+        let mut rig = CameraRig::new(1000.0, 1.0, MIN, MAX);
+        let target = 1500.0 - rig.dead_zone / 2.0 + rig.look_ahead;
+        let mut previous = rig.position();
+        for _ in 0..300 {
+            let position = rig.update(1500.0, 1.0, DT, MIN, MAX);
+            assert!(position >= previous, "camera moved back");
+            assert!(position <= target, "camera overshot the target");
+            previous = position;
+        }
+        assert!((previous - target).abs() < 0.01);
+    }
+
+    #[test]
+    fn look_ahead_follows_facing() {
+        let mut rig = CameraRig::new(1000.0, 1.0, MIN, MAX);
+        let right = settled(&mut rig, 1000.0, 1.0);
+        assert!((right - (1000.0 + rig.look_ahead)).abs() < 0.01);
+
+        let left = settled(&mut rig, 1000.0, -1.0);
+        assert!((left - (1000.0 - rig.look_ahead)).abs() < 0.01);
+    }
+
+    #[test]
+    fn shake_returns_to_zero_after_duration() {
+        let mut rig = CameraRig::new(1000.0, 1.0, MIN, MAX);
+        rig.shake(CAMERA_SHAKE_AMPLITUDE, CAMERA_SHAKE_TIME);
+        rig.update(1000.0, 1.0, DT, MIN, MAX);
+        assert_ne!(rig.shake_offset(), Vec2::ZERO);
+
+        let mut time = DT;
+        while time < CAMERA_SHAKE_TIME {
+            let offset = rig.shake_offset();
+            assert!(offset.x.abs() <= CAMERA_SHAKE_AMPLITUDE);
+            assert!(offset.y.abs() <= CAMERA_SHAKE_AMPLITUDE);
+            rig.update(1000.0, 1.0, DT, MIN, MAX);
+            time += DT;
+        }
+        assert_eq!(rig.shake_offset(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn pan_holds_on_target_and_returns() {
+        let mut rig = CameraRig::new(1000.0, 1.0, MIN, MAX);
+        let player_target = rig.position();
+        rig.pan_to(2000.0, 0.5);
+
+        let mut time = 0.0;
+        while (rig.position() - 2000.0).abs() >= 1.0 {
+            rig.update(1000.0, 1.0, DT, MIN, MAX);
+            time += DT;
+            assert!(time < 5.0, "pan never reached the target");
+        }
+
+        // Stays on the target for the hold time
+        for _ in 0..25 {
+            rig.update(1000.0, 1.0, DT, MIN, MAX);
+            assert!((rig.position() - 2000.0).abs() < 1.0);
+        }
+
+        let back = settled(&mut rig, 1000.0, 1.0);
+        assert!((back - player_target).abs() < 0.01);
+    }
+}
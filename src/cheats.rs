@@ -0,0 +1,10 @@
+use quad::prelude::*;
+
+/// Debug switches, set from the console. The hit map overlay can also be toggled with F3.
+#[derive(Resource, Default)]
+pub struct Cheats {
+    pub god: bool,
+    pub noclip: bool,
+    #[cfg(feature = "console")]
+    pub hit_map: bool,
+}
@@ -0,0 +1,451 @@
+use std::{fs, path::Path};
+
+use quad::prelude::*;
+
+use crate::{
+    input::{Action, Bindings, key_name, parse_key},
+    key_value,
+};
+
+pub const CONFIG_PATH: &str = "mouse1rs.cfg";
+pub const MAX_VOLUME: u32 = 10;
+pub const WINDOW_SIZES: [(u32, u32); 5] = [
+    (640, 400),
+    (960, 600),
+    (1280, 800),
+    (1600, 1000),
+    (1920, 1200),
+];
+
+/// Setting with a fixed set of values which can be cycled through in the settings scene.
+pub trait Choice: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+
+    fn name(self) -> &'static str;
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|value| value.name() == name)
+    }
+
+    fn cycle(self, forward: bool) -> Self {
+        let count = Self::ALL.len();
+        let index = Self::ALL
+            .iter()
+            .position(|&value| value == self)
+            .unwrap_or(0);
+        let next = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        Self::ALL[next]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DisplayMode {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl Choice for DisplayMode {
+    const ALL: &'static [Self] = &[Self::Windowed, Self::Borderless, Self::Fullscreen];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Windowed => "windowed",
+            Self::Borderless => "borderless",
+            Self::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Scaling {
+    Fit,
+    Integer,
+}
+
+impl Scaling {
+    pub fn zoom(self, zoom: f32) -> f32 {
+        match self {
+            Self::Fit => zoom,
+            Self::Integer => zoom.floor().max(1.0),
+        }
+    }
+}
+
+impl Choice for Scaling {
+    const ALL: &'static [Self] = &[Self::Fit, Self::Integer];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Fit => "fit",
+            Self::Integer => "integer",
+        }
+    }
+}
+
+/// Post-processing of the level view.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Filter {
+    None,
+    Scanlines,
+    /// Scanlines darkened towards the border, with the corners of the screen rounded off.
+    Vignette,
+    /// Limits the player sheet and the flat sprite colors to the original palette,
+    /// the level art is drawn in it already.
+    Palette,
+}
+
+impl Choice for Filter {
+    const ALL: &'static [Self] = &[Self::None, Self::Scanlines, Self::Vignette, Self::Palette];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Scanlines => "scanlines",
+            Self::Vignette => "vignette",
+            Self::Palette => "palette",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Controls {
+    Arrows,
+    Wasd,
+}
+
+impl Choice for Controls {
+    const ALL: &'static [Self] = &[Self::Arrows, Self::Wasd];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Arrows => "arrows",
+            Self::Wasd => "wasd",
+        }
+    }
+}
+
+/// Player movement model, classic keeps the constant speeds of the original game.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Physics {
+    Classic,
+    Momentum,
+}
+
+impl Choice for Physics {
+    const ALL: &'static [Self] = &[Self::Classic, Self::Momentum];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "classic",
+            Self::Momentum => "momentum",
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Language {
+    Czech,
+    English,
+}
+
+impl Choice for Language {
+    const ALL: &'static [Self] = &[Self::Czech, Self::English];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Czech => "cs",
+            Self::English => "en",
+        }
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Debug)]
+pub struct Config {
+    pub display_mode: DisplayMode,
+    pub window_size: (u32, u32),
+    /// Position of the windowed mode, unknown until the window is first moved.
+    pub window_position: Option<(i32, i32)>,
+    pub scaling: Scaling,
+    pub filter: Filter,
+    pub controls: Controls,
+    pub bindings: Bindings,
+    pub language: Language,
+    pub physics: Physics,
+    pub sound_volume: u32,
+    pub music_volume: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            display_mode: DisplayMode::Windowed,
+            window_size: (960, 600),
+            window_position: None,
+            scaling: Scaling::Fit,
+            filter: Filter::None,
+            controls: Controls::Arrows,
+            bindings: Bindings::preset(Controls::Arrows),
+            language: Language::Czech,
+            physics: Physics::Classic,
+            sound_volume: MAX_VOLUME,
+            music_volume: MAX_VOLUME / 2,
+        }
+    }
+}
+
+impl Config {
+    /// Missing file or invalid entries fall back to the defaults, the game should start regardless.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let mut config = Self::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return config,
+        };
+        let pairs = match key_value::parse(&text) {
+            Ok(pairs) => pairs,
+            Err(error) => {
+                log::warn!("Unable to parse configuration: {error}");
+                return config;
+            }
+        };
+
+        // Key bindings override the preset no matter in which order they are listed
+        let mut bindings = Vec::new();
+        for (key, value) in pairs {
+            let valid = match key.strip_prefix("bind.") {
+                Some(action) => Action::parse(action)
+                    .zip(parse_keys(value))
+                    .map(|binding| bindings.push(binding))
+                    .is_some(),
+                None => config.set(key, value),
+            };
+            if !valid {
+                log::warn!("Invalid configuration entry: {key} = {value}");
+            }
+        }
+
+        config.bindings = Bindings::preset(config.controls);
+        for (action, keys) in bindings {
+            config.bindings.set(action, keys);
+        }
+        config
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let (width, height) = self.window_size;
+        let mut text = format!(
+            "display_mode = {}\nwindow_size = {}, {}\nscaling = {}\nfilter = {}\nlanguage = {}\nphysics = {}\nsound_volume = {}\nmusic_volume = {}\ncontrols = {}\n",
+            self.display_mode.name(),
+            width,
+            height,
+            self.scaling.name(),
+            self.filter.name(),
+            self.language.name(),
+            self.physics.name(),
+            self.sound_volume,
+            self.music_volume,
+            self.controls.name(),
+        );
+        if let Some((x, y)) = self.window_position {
+            text.push_str(&format!("window_position = {}, {}\n", x, y));
+        }
+        // Only changes are saved, so editing the controls preset in the file takes effect
+        let preset = Bindings::preset(self.controls);
+        for action in Action::ALL {
+            if self.bindings.keys(action) == preset.keys(action) {
+                continue;
+            }
+            let keys: Vec<&str> = self
+                .bindings
+                .keys(action)
+                .iter()
+                .filter_map(|&key| key_name(key))
+                .collect();
+            text.push_str(&format!("bind.{} = {}\n", action.name(), keys.join(", ")));
+        }
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn set_controls(&mut self, controls: Controls) {
+        self.controls = controls;
+        self.bindings = Bindings::preset(controls);
+    }
+
+    pub fn cycle_window_size(&mut self, forward: bool) {
+        let count = WINDOW_SIZES.len();
+        let index = WINDOW_SIZES
+            .iter()
+            .position(|&size| size == self.window_size)
+            .unwrap_or(1);
+        let next = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+        self.window_size = WINDOW_SIZES[next];
+    }
+
+    pub fn apply_window(&self, window: &mut Window) {
+        let (width, height) = self.window_size;
+        window.set_mode(match self.display_mode {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        });
+        if self.display_mode == DisplayMode::Windowed {
+            window.set_resolution(width as f32, height as f32);
+            if let Some((x, y)) = self.window_position {
+                window.set_position(PhysicalPosition { x, y });
+            }
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> bool {
+        match key {
+            "display_mode" => DisplayMode::parse(value).map(|mode| self.display_mode = mode),
+            "window_size" => key_value::parse_list::<u32>(value)
+                .ok()
+                .filter(|size| size.len() == 2 && size[0] > 0 && size[1] > 0)
+                .map(|size| self.window_size = (size[0], size[1])),
+            "window_position" => key_value::parse_list::<i32>(value)
+                .ok()
+                .filter(|position| position.len() == 2)
+                .map(|position| self.window_position = Some((position[0], position[1]))),
+            "scaling" => Scaling::parse(value).map(|scaling| self.scaling = scaling),
+            "filter" => Filter::parse(value).map(|filter| self.filter = filter),
+            "controls" => Controls::parse(value).map(|controls| self.controls = controls),
+            "language" => Language::parse(value).map(|language| self.language = language),
+            "physics" => Physics::parse(value).map(|physics| self.physics = physics),
+            "sound_volume" => parse_volume(value).map(|volume| self.sound_volume = volume),
+            "music_volume" => parse_volume(value).map(|volume| self.music_volume = volume),
+            _ => None,
+        }
+        .is_some()
+    }
+}
+
+fn parse_volume(value: &str) -> Option<u32> {
+    value.parse().ok().filter(|&volume| volume <= MAX_VOLUME)
+}
+
+/// Volume one step up or down, stops at the ends of the range.
+pub fn step_volume(volume: u32, forward: bool) -> u32 {
+    if forward {
+        (volume + 1).min(MAX_VOLUME)
+    } else {
+        volume.saturating_sub(1)
+    }
+}
+
+fn parse_keys(value: &str) -> Option<Vec<KeyCode>> {
+    value
+        .split(',')
+        .map(|name| parse_key(name.trim()))
+        .collect::<Option<Vec<_>>>()
+        .filter(|keys| !keys.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::key_value::temp_path;
+
+    fn load_text(name: &str, text: &str) -> Config {
+        let path = temp_path(name);
+        fs::write(&path, text).unwrap();
+        let config = Config::load(&path);
+        fs::remove_file(path).unwrap();
+        config
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut config = Config {
+            display_mode: DisplayMode::Borderless,
+            window_size: (1280, 800),
+            window_position: Some((-20, 40)),
+            scaling: Scaling::Integer,
+            filter: Filter::Scanlines,
+            language: Language::English,
+            physics: Physics::Momentum,
+            sound_volume: 3,
+            music_volume: 0,
+            ..Default::default()
+        };
+        config.set_controls(Controls::Wasd);
+        config
+            .bindings
+            .set(Action::Jump, vec![KeyCode::KeyK, KeyCode::Space]);
+
+        let path = temp_path("round_trip.cfg");
+        config.save(&path).unwrap();
+        let loaded = Config::load(&path);
+        fs::remove_file(path).unwrap();
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn only_changed_bindings_are_saved() {
+        let mut config = Config::default();
+        config.set_controls(Controls::Wasd);
+        config.bindings.set(Action::Jump, vec![KeyCode::KeyK]);
+
+        let path = temp_path("bindings.cfg");
+        config.save(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(path).unwrap();
+        let bindings: Vec<&str> = text
+            .lines()
+            .filter(|line| line.starts_with("bind."))
+            .collect();
+        assert_eq!(bindings, ["bind.jump = K"]);
+    }
+
+    #[test]
+    fn controls_preset_applies_on_load() {
+        let config = load_text("preset.cfg", "controls = wasd\nbind.jump = K\n");
+        let mut expected = Bindings::preset(Controls::Wasd);
+        expected.set(Action::Jump, vec![KeyCode::KeyK]);
+        assert_eq!(config.bindings, expected);
+    }
+
+    #[test]
+    fn missing_file_gives_defaults() {
+        assert_eq!(Config::load(temp_path("missing.cfg")), Config::default());
+    }
+
+    #[test]
+    fn invalid_entries_fall_back_to_defaults() {
+        let config = load_text(
+            "invalid.cfg",
+            "scaling = huge\n\
+             unknown = 1\n\
+             sound_volume = 11\n\
+             window_size = 0, 600\n\
+             bind.jump = NoSuchKey\n\
+             bind.fly = Space\n\
+             physics = momentum\n",
+        );
+        assert_eq!(
+            config,
+            Config {
+                physics: Physics::Momentum,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn unparsable_file_gives_defaults() {
+        let config = load_text("unparsable.cfg", "physics = momentum\nnot a pair\n");
+        assert_eq!(config, Config::default());
+    }
+}
@@ -0,0 +1,278 @@
+use quad::prelude::*;
+
+use crate::{
+    cheats::Cheats,
+    constant::*,
+    fonts::FontChain,
+    input::update_actions,
+    level::Level,
+    mouse::{Lifes, render_lifes},
+    pause::PauseAction,
+    player::Player,
+    ui,
+    window::update_window,
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+const HISTORY_LENGTH: usize = 8;
+const TEXT_SIZE: f32 = 20.0;
+
+const CHAR_KEYS: [(KeyCode, char); 39] = [
+    (KeyCode::KeyA, 'a'),
+    (KeyCode::KeyB, 'b'),
+    (KeyCode::KeyC, 'c'),
+    (KeyCode::KeyD, 'd'),
+    (KeyCode::KeyE, 'e'),
+    (KeyCode::KeyF, 'f'),
+    (KeyCode::KeyG, 'g'),
+    (KeyCode::KeyH, 'h'),
+    (KeyCode::KeyI, 'i'),
+    (KeyCode::KeyJ, 'j'),
+    (KeyCode::KeyK, 'k'),
+    (KeyCode::KeyL, 'l'),
+    (KeyCode::KeyM, 'm'),
+    (KeyCode::KeyN, 'n'),
+    (KeyCode::KeyO, 'o'),
+    (KeyCode::KeyP, 'p'),
+    (KeyCode::KeyQ, 'q'),
+    (KeyCode::KeyR, 'r'),
+    (KeyCode::KeyS, 's'),
+    (KeyCode::KeyT, 't'),
+    (KeyCode::KeyU, 'u'),
+    (KeyCode::KeyV, 'v'),
+    (KeyCode::KeyW, 'w'),
+    (KeyCode::KeyX, 'x'),
+    (KeyCode::KeyY, 'y'),
+    (KeyCode::KeyZ, 'z'),
+    (KeyCode::Digit0, '0'),
+    (KeyCode::Digit1, '1'),
+    (KeyCode::Digit2, '2'),
+    (KeyCode::Digit3, '3'),
+    (KeyCode::Digit4, '4'),
+    (KeyCode::Digit5, '5'),
+    (KeyCode::Digit6, '6'),
+    (KeyCode::Digit7, '7'),
+    (KeyCode::Digit8, '8'),
+    (KeyCode::Digit9, '9'),
+    (KeyCode::Space, ' '),
+    (KeyCode::Minus, '-'),
+    (KeyCode::Period, '.'),
+];
+
+const HELP: &str = "tp <x> <y>, lives <n>, level <n>, god, noclip, hitmap, player";
+
+pub fn is_toggled(keyboard: &KeyboardInput) -> bool {
+    keyboard.just_pressed(TOGGLE_KEY)
+}
+
+/// Command line state kept between openings of the console.
+#[derive(Resource, Default)]
+pub struct Console {
+    input: String,
+    history: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        log::info!("{line}");
+        self.history.push(line);
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.remove(0);
+        }
+    }
+
+    fn text(&self) -> String {
+        let mut lines = self.history.clone();
+        lines.push(format!("> {}_", self.input));
+        lines.join("\n")
+    }
+}
+
+pub struct ConsoleSchedule {
+    start: Schedule<(), SceneResult>,
+    update: Schedule<(), SceneResult>,
+}
+
+#[derive(Component)]
+struct ConsoleText;
+
+#[derive(Resource)]
+struct ConsoleData {
+    root: Entity,
+}
+
+#[derive(Default)]
+pub struct ConsoleScene {
+    schedule: Option<ConsoleSchedule>,
+}
+
+impl Scene for ConsoleScene {
+    fn update(&mut self, stage: SceneStage, world: &mut World) -> SceneResult {
+        let schedule = self.schedule.get_or_insert_with(|| ConsoleSchedule {
+            start: Scheduler::single(console_start),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(render_lifes)
+                .add(console_update)
+                .build(),
+        });
+
+        match stage {
+            SceneStage::Start => schedule.start.run(world),
+            SceneStage::Update => schedule.update.run(world),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn console_start(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    console: Res<Console>,
+) -> SceneResult {
+    let root = ui::screen(&mut commands, Color::rgba(0.0, 0.0, 0.0, 0.7), |parent| {
+        ui::column(parent, AlignItems::FlexStart, |parent| {
+            parent
+                .spawn()
+                .insert_bundle(UiTextBundle {
+                    text: Text {
+                        sections: fonts.sections(&console.text(), TEXT_SIZE, Color::GREEN),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(ConsoleText);
+        });
+    });
+
+    commands.insert_resource(ConsoleData { root });
+
+    SceneResult::Ok(SceneStage::Update)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn console_update(
+    mut commands: Commands,
+    keyboard: Res<KeyboardInput>,
+    fonts: Res<FontChain>,
+    data: Res<ConsoleData>,
+    mut console: ResMut<Console>,
+    mut cheats: ResMut<Cheats>,
+    mut lifes: ResMut<Lifes>,
+    mut level: ResMut<Level>,
+    mut player: Query<&mut Player>,
+    mut text: Query<&mut Text, With<ConsoleText>>,
+) -> SceneResult {
+    let mut action = None;
+    if keyboard.just_pressed(TOGGLE_KEY) || keyboard.just_pressed(KeyCode::Escape) {
+        action = Some(PauseAction::Resume);
+    } else if keyboard.just_pressed(KeyCode::Enter) {
+        let line = std::mem::take(&mut console.input);
+        console.print(format!("> {line}"));
+        let mut player = player.single_mut();
+        action = match execute(&line, &mut cheats, &mut lifes, &mut level, &mut player) {
+            Ok(output) => {
+                if let Some(output) = output.message {
+                    console.print(output);
+                }
+                output.action
+            }
+            Err(error) => {
+                console.print(error);
+                None
+            }
+        };
+    } else if keyboard.just_pressed(KeyCode::Backspace) {
+        console.input.pop();
+    } else {
+        for (key, c) in CHAR_KEYS {
+            if keyboard.just_pressed(key) {
+                console.input.push(c);
+            }
+        }
+    }
+
+    if let Some(action) = action {
+        commands.entity(data.root).despawn_recursive();
+        commands.remove_resource::<ConsoleData>();
+        commands.insert_resource(action);
+        return SceneResult::Pop(SceneStage::Resume);
+    }
+
+    text.single_mut().sections = fonts.sections(&console.text(), TEXT_SIZE, Color::GREEN);
+    SceneResult::Ok(SceneStage::Update)
+}
+
+#[derive(Default)]
+struct CommandOutput {
+    message: Option<String>,
+    action: Option<PauseAction>,
+}
+
+impl CommandOutput {
+    fn message(message: String) -> Self {
+        Self {
+            message: Some(message),
+            action: None,
+        }
+    }
+
+    fn toggle(name: &str, value: &mut bool) -> Self {
+        *value = !*value;
+        Self::message(format!("{name} {}", if *value { "on" } else { "off" }))
+    }
+}
+
+fn argument<T: std::str::FromStr>(args: &[&str], index: usize) -> Result<T, String> {
+    let arg = args.get(index).ok_or("Missing argument")?;
+    arg.parse().map_err(|_| format!("Invalid argument: {arg}"))
+}
+
+fn execute(
+    line: &str,
+    cheats: &mut Cheats,
+    lifes: &mut Lifes,
+    level: &mut Level,
+    player: &mut Player,
+) -> Result<CommandOutput, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let Some((&command, args)) = words.split_first() else {
+        return Ok(CommandOutput::default());
+    };
+
+    let output = match command {
+        "tp" => {
+            player.position = Vec2::new(argument(args, 0)?, argument(args, 1)?);
+            // A fall starts from the new position, not from where the player was
+            player.fall_top = player.position.y;
+            player.velocity = Vec2::ZERO;
+            CommandOutput::message(format!("Teleported to {}", player.position))
+        }
+        "lives" => {
+            lifes.count = argument::<usize>(args, 0)?.clamp(1, MAX_LIFE_COUNT);
+            CommandOutput::message(format!("Lives set to {}", lifes.count))
+        }
+        "level" => {
+            let number: usize = argument(args, 0)?;
+            if !(1..=LEVEL_COUNT).contains(&number) {
+                return Err(format!("Level must be between 1 and {LEVEL_COUNT}"));
+            }
+            level.0 = number - 1;
+            CommandOutput {
+                message: None,
+                action: Some(PauseAction::Restart),
+            }
+        }
+        "god" => CommandOutput::toggle("God mode", &mut cheats.god),
+        "noclip" => CommandOutput::toggle("Noclip", &mut cheats.noclip),
+        "hitmap" => CommandOutput::toggle("Hit map", &mut cheats.hit_map),
+        "player" => CommandOutput::message(format!(
+            "{:?} {:?} at {} jump {:.1}",
+            player.state, player.orientation, player.position, player.jump_phase
+        )),
+        "help" => CommandOutput::message(HELP.to_string()),
+        _ => return Err(format!("Unknown command: {command}")),
+    };
+    Ok(output)
+}
@@ -3,12 +3,33 @@ pub const SCREEN_WIDTH: f32 = 320.0;
 pub const SCREEN_HEIGHT: f32 = 192.0;
 pub const SCREEN_COUNT: f32 = 10.0;
 pub const TOTAL_SCREEN_WIDTH: f32 = SCREEN_WIDTH * SCREEN_COUNT;
-pub const BCG_SCREEN_COUNT: f32 = SCREEN_COUNT / 2.0 + 1.0;
 pub const PLAYER_HEIGHT: f32 = 16.0;
 pub const PLAYER_WIDTH: f32 = 10.0;
 pub const PLAYER_Y_OFFSET: f32 = SCREEN_HEIGHT / 2.0 - PLAYER_HEIGHT / 2.0;
 pub const PLAYER_X_OFFSET: f32 = -(TOTAL_SCREEN_WIDTH / 2.0 - PLAYER_WIDTH / 2.0);
 pub const PLAYER_JUMP_MAX: f32 = 40.0;
+pub const PLAYER_JUMP_MIN: f32 = 12.0;
+pub const PLAYER_COYOTE_TIME: f32 = 0.1;
+pub const PLAYER_JUMP_BUFFER: f32 = 0.12;
+pub const PLAYER_SQUASH_DELAY: f32 = 0.6;
+pub const DEATH_FREEZE_TIME: f32 = 0.25;
+pub const DEATH_ANIMATION_TIME: f32 = 0.75;
+pub const EXIT_TIME: f32 = 0.8;
 pub const UPDATE_SPEED: f32 = 60.0;
-pub const ANIMATION_COUNT: f32 = 8.0;
+pub const PLAYER_ACCELERATION: f32 = 360.0;
+pub const PLAYER_FRICTION: f32 = 480.0;
+pub const PLAYER_GRAVITY: f32 = 400.0;
+// Reaches PLAYER_JUMP_MAX, sqrt(2 * PLAYER_GRAVITY * PLAYER_JUMP_MAX)
+pub const PLAYER_JUMP_VELOCITY: f32 = 180.0;
+pub const PLAYER_JUMP_CUT_VELOCITY: f32 = 60.0;
+pub const PLAYER_TERMINAL_VELOCITY: f32 = 150.0;
 pub const ANIMATION_SPEED: f32 = 1.0 / 7.0;
+pub const CAMERA_DEAD_ZONE: f32 = 80.0;
+pub const CAMERA_LOOK_AHEAD: f32 = 30.0;
+pub const CAMERA_SMOOTH_TIME: f32 = 0.3;
+pub const CAMERA_SHAKE_AMPLITUDE: f32 = 3.0;
+pub const CAMERA_SHAKE_TIME: f32 = 0.3;
+pub const CAMERA_SHAKE_FREQUENCY: f32 = 40.0;
+pub const TRANSITION_TIME: f32 = 0.6;
+pub const LEVEL_COUNT: usize = 5;
+pub const MAX_LIFE_COUNT: usize = 9;
@@ -0,0 +1,154 @@
+use quad::prelude::*;
+
+use crate::{
+    cheats::Cheats,
+    constant::*,
+    hit_map::{HitMap, Probe},
+    level::{Level, SceneRoot},
+    mouse::GameAssets,
+    player::Player,
+};
+
+#[derive(Component)]
+pub struct DebugOverlay {
+    /// Hit map image, removed together with the overlay.
+    texture: Handle<Image>,
+}
+
+#[derive(Component)]
+pub struct ProbePoint;
+
+fn kind_color(kind: Option<u8>) -> [u8; 4] {
+    match kind {
+        Some(1) => [0, 0, 255, 128],
+        Some(2) => [255, 0, 0, 160],
+        Some(3) => [0, 255, 0, 160],
+        _ => [0, 0, 0, 0],
+    }
+}
+
+pub fn hit_map_image(hit_map: &HitMap) -> Image {
+    let width = TOTAL_SCREEN_WIDTH as u32;
+    let height = SCREEN_HEIGHT as u32;
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| kind_color(hit_map.kind_at(x as f32, y as f32)))
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Centre of a level pixel in the coordinates of the scene root.
+fn pixel_translation(point: Vec2, z: f32) -> Vec3 {
+    Vec3::new(
+        point.x + 0.5 + PLAYER_X_OFFSET - PLAYER_WIDTH / 2.0,
+        PLAYER_Y_OFFSET + PLAYER_HEIGHT / 2.0 - point.y - 0.5,
+        z,
+    )
+}
+
+/// Shows the hit map on top of the level foreground, the overlay is only built while it is enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn update_debug_overlay(
+    mut commands: Commands,
+    cheats: Res<Cheats>,
+    game_assets: Res<GameAssets>,
+    level: Res<Level>,
+    hit_map_assets: Res<Assets<HitMap>>,
+    mut images: ResMut<Assets<Image>>,
+    overlays: Query<(Entity, &DebugOverlay)>,
+    root: Query<Entity, With<SceneRoot>>,
+) {
+    let overlay = overlays.iter().next();
+    match (cheats.hit_map, overlay) {
+        (true, None) => {
+            let hit_map = hit_map_assets
+                .get(&game_assets.level[level.0].hit_map)
+                .unwrap();
+            let texture = images.add(hit_map_image(hit_map));
+            let overlay = commands
+                .spawn()
+                .insert(DebugOverlay {
+                    texture: texture.clone(),
+                })
+                .insert_bundle(SpatialBundle::default())
+                .with_children(|parent| {
+                    parent.spawn_bundle(SpriteBundle {
+                        texture,
+                        transform: Transform::from_xyz(0.0, 0.0, 1.5),
+                        ..Default::default()
+                    });
+                })
+                .id();
+            commands.entity(root.single()).add_child(overlay);
+        }
+        (false, Some((entity, overlay))) => {
+            images.remove(&overlay.texture);
+            commands.entity(entity).despawn_recursive();
+        }
+        _ => {}
+    }
+}
+
+/// Marks the points the player probed during this frame's update, the ones which hit the map
+/// are drawn in red.
+pub fn update_probe_points(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    level: Res<Level>,
+    hit_map_assets: Res<Assets<HitMap>>,
+    player_query: Query<&Player>,
+    overlays: Query<Entity, With<DebugOverlay>>,
+    probe_points: Query<Entity, With<ProbePoint>>,
+) {
+    // Recursive despawn also removes the points from the children of the overlay
+    for entity in probe_points.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(overlay) = overlays.iter().next() else {
+        return;
+    };
+    let player = player_query.single();
+    let hit_map = hit_map_assets
+        .get(&game_assets.level[level.0].hit_map)
+        .unwrap();
+
+    let mut points: Vec<(Probe, Vec2)> = Vec::new();
+    for &(probe, origin) in &player.probes {
+        for point in probe.points(origin.x, origin.y) {
+            if !points.contains(&(probe, point)) {
+                points.push((probe, point));
+            }
+        }
+    }
+
+    commands.entity(overlay).with_children(|parent| {
+        for (probe, point) in points {
+            let color = if hit_map.probe_hits(probe, point) {
+                Color::RED
+            } else {
+                Color::YELLOW
+            };
+            parent
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::ONE),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(pixel_translation(point, 3.0)),
+                    ..Default::default()
+                })
+                .insert(ProbePoint);
+        }
+    });
+}
@@ -0,0 +1,184 @@
+use quad::prelude::*;
+
+use crate::{
+    audio::play_menu_sounds,
+    config::Choice,
+    constant::TRANSITION_TIME,
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
+    level::Level,
+    level_opening::LevelOpeningScene,
+    locale::Localization,
+    mouse::Lifes,
+    progress::{PROGRESS_PATH, Progress},
+    transition::{TransitionEffect, TransitionScene},
+    ui::{self, MenuItem, MenuList},
+    window::update_window,
+};
+
+/// Chosen when starting a new game, kept for Continue and the level select.
+#[derive(Resource, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Choice for Difficulty {
+    const ALL: &'static [Self] = &[Self::Easy, Self::Normal, Self::Hard];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Normal => "normal",
+            Self::Hard => "hard",
+        }
+    }
+}
+
+impl Difficulty {
+    pub fn lives(self) -> usize {
+        match self {
+            Self::Easy => 9,
+            Self::Normal => 5,
+            Self::Hard => 3,
+        }
+    }
+
+    pub fn has_checkpoints(self) -> bool {
+        self != Self::Hard
+    }
+
+    /// Multiplier of the player's movement speed and acceleration.
+    pub fn speed(self) -> f32 {
+        match self {
+            Self::Easy => 0.85,
+            Self::Normal => 1.0,
+            Self::Hard => 1.15,
+        }
+    }
+
+    /// See `HitMap::check_dead`.
+    pub fn dead_tolerance(self) -> i32 {
+        match self {
+            Self::Easy => 2,
+            Self::Normal => 0,
+            Self::Hard => -2,
+        }
+    }
+
+    /// Fall height killing the player in levels which do not set their own.
+    pub fn lethal_fall(self) -> Option<f32> {
+        match self {
+            Self::Hard => Some(96.0),
+            _ => None,
+        }
+    }
+}
+
+pub struct DifficultySchedule {
+    start: Schedule<(), SceneResult>,
+    update: Schedule<(), SceneResult>,
+}
+
+#[derive(Resource)]
+struct DifficultyData {
+    root: Entity,
+    menu: MenuList,
+}
+
+#[derive(Default)]
+pub struct DifficultyScene {
+    schedule: Option<DifficultySchedule>,
+}
+
+impl Scene for DifficultyScene {
+    fn update(&mut self, stage: SceneStage, world: &mut World) -> SceneResult {
+        let schedule = self.schedule.get_or_insert_with(|| DifficultySchedule {
+            start: Scheduler::single(difficulty_start),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(play_menu_sounds)
+                .add(difficulty_update)
+                .build(),
+        });
+
+        match stage {
+            SceneStage::Start => schedule.start.run(world),
+            SceneStage::Update => schedule.update.run(world),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn difficulty_start(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
+    difficulty: Res<Difficulty>,
+) -> SceneResult {
+    let mut menu = MenuList::new(Difficulty::ALL.len());
+    menu.select(
+        Difficulty::ALL
+            .iter()
+            .position(|&d| d == *difficulty)
+            .unwrap_or(0),
+    );
+    let root = ui::screen(&mut commands, Color::NONE, |parent| {
+        ui::column(parent, AlignItems::FlexStart, |parent| {
+            let keys: Vec<String> = Difficulty::ALL
+                .iter()
+                .map(|difficulty| format!("difficulty.{}", difficulty.name()))
+                .collect();
+            let labels: Vec<&str> = keys.iter().map(|key| strings.text(key)).collect();
+            menu.spawn(parent, &fonts, &labels);
+            ui::title(parent, &fonts, strings.text("difficulty.title"));
+        });
+    });
+
+    commands.insert_resource(DifficultyData { root, menu });
+
+    SceneResult::Ok(SceneStage::Update)
+}
+
+fn difficulty_update(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut data: ResMut<DifficultyData>,
+    mut lifes: ResMut<Lifes>,
+    mut progress: ResMut<Progress>,
+    mut items: Query<(&MenuItem, &mut Text)>,
+) -> SceneResult {
+    if data.menu.navigate(&actions) {
+        data.menu.highlight(&mut items);
+    }
+
+    if actions.just_pressed(Action::Back) {
+        commands.entity(data.root).despawn_recursive();
+        commands.remove_resource::<DifficultyData>();
+        SceneResult::Pop(SceneStage::Resume)
+    } else if actions.just_pressed(Action::Confirm) {
+        let difficulty = Difficulty::ALL[data.menu.selected()];
+        commands.remove_resource::<DifficultyData>();
+        commands.insert_resource(difficulty);
+        commands.insert_resource(Level(0));
+        lifes.count = difficulty.lives();
+        // Continue keeps playing on the chosen difficulty after a restart
+        progress.difficulty = difficulty;
+        if let Err(error) = progress.save(PROGRESS_PATH) {
+            log::error!("Unable to save progress: {error}");
+        }
+        SceneResult::Replace(
+            TransitionScene::new(
+                TransitionEffect::Fade,
+                TRANSITION_TIME,
+                Box::<LevelOpeningScene>::default(),
+            )
+            .covering(data.root),
+            SceneStage::Start,
+        )
+    } else {
+        SceneResult::Ok(SceneStage::Update)
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use quad::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    ty::BoxedFuture,
+};
+
+use crate::constant::*;
+
+pub const PALETTE_PATH: &str = "vga.pal";
+/// Opacity of the dark half of each pixel row.
+const SCANLINE_DARKNESS: f32 = 0.35;
+/// How much the corners of the screen mask are rounded off, 0 keeps them square.
+const VIGNETTE_CORNER: f32 = 0.08;
+/// Darkening towards the screen border.
+const VIGNETTE_DARKNESS: f32 = 0.4;
+
+/// The 256 colors of the original VGA palette.
+pub struct Palette {
+    colors: Vec<[u8; 3]>,
+}
+
+impl Palette {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        if bytes.len() != 256 * 3 {
+            bail!("Invalid palette size: {}", bytes.len());
+        }
+        let colors = bytes
+            .chunks_exact(3)
+            .map(|rgb| [rgb[0], rgb[1], rgb[2]])
+            .collect();
+        Ok(Self { colors })
+    }
+
+    pub fn nearest(&self, rgb: [u8; 3]) -> [u8; 3] {
+        let distance = |color: &[u8; 3]| -> i32 {
+            color
+                .iter()
+                .zip(rgb)
+                .map(|(&a, b)| (a as i32 - b as i32).pow(2))
+                .sum()
+        };
+        *self
+            .colors
+            .iter()
+            .min_by_key(|color| distance(color))
+            .unwrap()
+    }
+
+    /// Nearest palette color keeping the alpha.
+    pub fn nearest_color(&self, color: Color) -> Color {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let [r, g, b] = self.nearest([channel(color.r()), channel(color.g()), channel(color.b())]);
+        Color::rgba(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            color.a(),
+        )
+    }
+
+    /// Replaces the color of every visible pixel of RGBA data by the nearest palette color.
+    pub fn quantize(&self, data: &mut [u8]) {
+        let mut cache = HashMap::new();
+        for pixel in data.chunks_exact_mut(4) {
+            if pixel[3] == 0 {
+                continue;
+            }
+            let rgb = [pixel[0], pixel[1], pixel[2]];
+            let nearest = *cache.entry(rgb).or_insert_with(|| self.nearest(rgb));
+            pixel[..3].copy_from_slice(&nearest);
+        }
+    }
+
+    pub fn quantize_image(&self, image: &Image) -> Image {
+        let mut image = image.clone();
+        self.quantize(&mut image.data);
+        image
+    }
+}
+
+/// Opacity of the scanline overlay on the given overlay row, there are two rows per pixel.
+pub fn scanline_alpha(row: usize) -> f32 {
+    if row % 2 == 1 { SCANLINE_DARKNESS } else { 0.0 }
+}
+
+/// Opacity of the vignette overlay at the normalized screen position, `x` and `y` go from 0 to 1.
+/// Black in the rounded off corners, darkened towards the border and with scanlines inside.
+pub fn vignette_alpha(x: f32, y: f32, row: usize) -> f32 {
+    let u = x * 2.0 - 1.0;
+    let v = y * 2.0 - 1.0;
+    // Pushed out the more the closer to a corner, so only the corners leave the screen
+    let u = u * (1.0 + VIGNETTE_CORNER * v * v);
+    let v = v * (1.0 + VIGNETTE_CORNER * u * u);
+    if u.abs() > 1.0 || v.abs() > 1.0 {
+        return 1.0;
+    }
+    let vignette = VIGNETTE_DARKNESS * (1.0 - (1.0 - u * u) * (1.0 - v * v));
+    1.0 - (1.0 - vignette) * (1.0 - scanline_alpha(row))
+}
+
+/// Black RGBA overlay covering one screen, with two rows per screen pixel for the scanlines.
+fn overlay_image(width: usize, alpha: impl Fn(f32, f32, usize) -> f32) -> Image {
+    let height = 2 * SCREEN_HEIGHT as usize;
+    let mut data = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        for column in 0..width {
+            let x = (column as f32 + 0.5) / width as f32;
+            let y = (row as f32 + 0.5) / height as f32;
+            let alpha = alpha(x, y, row).clamp(0.0, 1.0);
+            data.extend_from_slice(&[0, 0, 0, (alpha * 255.0).round() as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Scanlines don't change along a row, a single column is stretched over the screen.
+pub fn scanline_image() -> Image {
+    overlay_image(1, |_, _, row| scanline_alpha(row))
+}
+
+pub fn vignette_image() -> Image {
+    overlay_image(SCREEN_WIDTH as usize, vignette_alpha)
+}
+
+#[derive(Default)]
+pub struct PaletteLoader;
+
+impl AssetLoader for PaletteLoader {
+    fn load(
+        &self,
+        bytes: &[u8],
+        load_context: &mut LoadContext,
+    ) -> BoxedFuture<'_, anyhow::Result<()>> {
+        let result = Palette::from_bytes(bytes)
+            .map(|palette| load_context.set_default_asset(LoadedAsset::new(palette)));
+        Box::pin(async move { result })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pal"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mouse::asset_path;
+
+    fn gray_palette() -> Palette {
+        let bytes: Vec<u8> = (0..=255u8)
+            .flat_map(|value| [value, value, value])
+            .collect();
+        Palette::from_bytes(&bytes).unwrap()
+    }
+
+    #[test]
+    fn scanlines_darken_every_other_row() {
+        assert_eq!(scanline_alpha(0), 0.0);
+        assert_eq!(scanline_alpha(1), SCANLINE_DARKNESS);
+        assert_eq!(scanline_alpha(2), 0.0);
+        assert_eq!(scanline_alpha(3), SCANLINE_DARKNESS);
+    }
+
+    #[test]
+    fn vignette_is_clear_in_the_center_and_black_in_the_corners() {
+        assert!(vignette_alpha(0.5, 0.5, 0).abs() < 1e-6);
+        assert!((vignette_alpha(0.5, 0.5, 1) - SCANLINE_DARKNESS).abs() < 1e-6);
+        assert_eq!(vignette_alpha(0.0, 0.0, 0), 1.0);
+        assert_eq!(vignette_alpha(1.0, 1.0, 0), 1.0);
+    }
+
+    #[test]
+    fn vignette_darkens_towards_the_border() {
+        let mut previous = vignette_alpha(0.5, 0.5, 0);
+        for step in 1..=10 {
+            let alpha = vignette_alpha(0.5 + step as f32 * 0.05, 0.5, 0);
+            assert!(alpha >= previous);
+            previous = alpha;
+        }
+        assert!(previous > 0.0 && previous < 1.0);
+    }
+
+    #[test]
+    fn palette_rejects_wrong_size() {
+        assert!(Palette::from_bytes(&[0; 255 * 3]).is_err());
+    }
+
+    #[test]
+    fn vga_palette_loads() {
+        let bytes = std::fs::read(asset_path(PALETTE_PATH)).unwrap();
+        assert!(Palette::from_bytes(&bytes).is_ok());
+    }
+
+    #[test]
+    fn nearest_picks_the_closest_color() {
+        let palette = gray_palette();
+        assert_eq!(palette.nearest([40, 40, 40]), [40, 40, 40]);
+        assert_eq!(palette.nearest([10, 12, 11]), [11, 11, 11]);
+        assert_eq!(palette.nearest([255, 250, 255]), [253, 253, 253]);
+    }
+
+    #[test]
+    fn nearest_color_keeps_alpha() {
+        let palette = gray_palette();
+        let color =
+            palette.nearest_color(Color::rgba(10.0 / 255.0, 12.0 / 255.0, 11.0 / 255.0, 0.5));
+        assert_eq!(
+            color,
+            Color::rgba(11.0 / 255.0, 11.0 / 255.0, 11.0 / 255.0, 0.5)
+        );
+    }
+
+    #[test]
+    fn quantize_keeps_alpha_and_skips_transparent_pixels() {
+        let palette = gray_palette();
+        let mut data = [10, 12, 11, 128, 200, 10, 30, 0];
+        palette.quantize(&mut data);
+        assert_eq!(data, [11, 11, 11, 128, 200, 10, 30, 0]);
+    }
+}
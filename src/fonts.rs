@@ -0,0 +1,89 @@
+use ab_glyph::{Font as _, FontArc};
+use quad::prelude::*;
+
+/// Font chain used for UI text, fonts later in the list supply glyphs missing in the earlier ones.
+pub const FONTS: [&str; 2] = ["helvetica.ttf", "DejaVuSans.ttf"];
+
+/// Fonts in order of preference, every character is rendered with the first font that has a glyph for it.
+#[derive(Resource)]
+pub struct FontChain {
+    fonts: Vec<(Handle<Font>, FontArc)>,
+}
+
+impl FontChain {
+    pub fn new(fonts: Vec<(Handle<Font>, FontArc)>) -> Self {
+        Self { fonts }
+    }
+
+    /// Splits the text into sections, each of them rendered with a single font from the chain.
+    pub fn sections(&self, text: &str, font_size: f32, color: Color) -> Vec<TextSection> {
+        let mut sections: Vec<(usize, String)> = Vec::new();
+        for c in text.chars() {
+            let index = match sections.last() {
+                Some((last, _)) if c.is_whitespace() => *last,
+                _ => self.font_index(c),
+            };
+            match sections.last_mut() {
+                Some((last, value)) if *last == index => value.push(c),
+                _ => sections.push((index, c.to_string())),
+            }
+        }
+
+        sections
+            .into_iter()
+            .map(|(index, value)| TextSection {
+                value,
+                style: TextStyle {
+                    font: self.fonts[index].0.clone(),
+                    font_size,
+                    color,
+                },
+            })
+            .collect()
+    }
+
+    fn font_index(&self, c: char) -> usize {
+        self.fonts
+            .iter()
+            .position(|(_, font)| has_glyph(font, c))
+            .unwrap_or(0)
+    }
+}
+
+fn has_glyph(font: &FontArc, c: char) -> bool {
+    font.glyph_id(c).0 != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        config::{Choice, Language},
+        locale::StringTable,
+        mouse::asset_path,
+    };
+
+    #[test]
+    fn localized_glyphs_are_in_fonts() {
+        let fonts: Vec<FontArc> = FONTS
+            .iter()
+            .map(|path| FontArc::try_from_vec(fs::read(asset_path(path)).unwrap()).unwrap())
+            .collect();
+
+        let mut missing = Vec::new();
+        for &language in Language::ALL {
+            let path = StringTable::path(language);
+            let table = StringTable::from_bytes(&fs::read(asset_path(&path)).unwrap()).unwrap();
+            for (key, value) in table.iter() {
+                for c in value.chars().filter(|c| !c.is_whitespace()) {
+                    if !fonts.iter().any(|font| has_glyph(font, c)) {
+                        missing.push(format!("{path}: {key}: '{c}'"));
+                    }
+                }
+            }
+        }
+        assert!(missing.is_empty(), "Missing glyphs: {missing:?}");
+    }
+}
@@ -1,6 +1,13 @@
 use quad::prelude::*;
 
-use crate::{mouse::GameAssets, mouse::render_lifes};
+use crate::{
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
+    locale::Localization,
+    mouse::render_lifes,
+    ui,
+    window::update_window,
+};
 
 pub struct GameCompleteSchedule {
     start: Schedule<(), SceneResult>,
@@ -24,7 +31,11 @@ impl Scene for GameCompleteScene {
                 .add(render_lifes)
                 .add(game_complete_start)
                 .build(),
-            update: Scheduler::single(game_complete_update),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(game_complete_update)
+                .build(),
         });
 
         match stage {
@@ -35,78 +46,17 @@ impl Scene for GameCompleteScene {
     }
 }
 
-fn game_complete_start(mut commands: Commands, assets: Res<GameAssets>) -> SceneResult {
-    let root = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                position_type: PositionType::Absolute,
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                ..Default::default()
-            },
-            color: Color::NONE.into(),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: "Blahopreji dokoncil jsi hru!".to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::GREEN,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                        position_type: PositionType::Absolute,
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: "STISKNI ENTER".to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 25.0,
-                                    color: Color::ORANGE_RED,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-        })
-        .id();
+fn game_complete_start(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
+) -> SceneResult {
+    let root = ui::message_screen(
+        &mut commands,
+        &fonts,
+        &[(strings.text("game_complete.message"), Color::GREEN)],
+        strings.text("prompt.enter"),
+    );
 
     commands.insert_resource(GameCompleteData { root });
 
@@ -115,10 +65,10 @@ fn game_complete_start(mut commands: Commands, assets: Res<GameAssets>) -> Scene
 
 fn game_complete_update(
     mut commands: Commands,
-    keyboard: Res<KeyboardInput>,
+    actions: Res<ActionState>,
     data: Res<GameCompleteData>,
 ) -> SceneResult {
-    if keyboard.just_pressed(KeyCode::Enter) {
+    if actions.just_pressed(Action::Confirm) {
         commands.entity(data.root).despawn_recursive();
         commands.remove_resource::<GameCompleteData>();
         SceneResult::Pop(SceneStage::Resume)
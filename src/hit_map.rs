@@ -2,6 +2,7 @@ use std::iter::repeat_n;
 
 use quad::{
     asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::Vec2,
     ty::BoxedFuture,
 };
 
@@ -22,6 +23,16 @@ impl HitMap {
         Self { map }
     }
 
+    /// Map with the kind of every pixel given by `kind(x, y)`.
+    #[cfg(test)]
+    pub fn from_fn(kind: impl Fn(usize, usize) -> u8) -> Self {
+        let map = (0..SCREEN_HEIGHT as usize)
+            .flat_map(|y| (0..ROW_PIXEL_COUNT).map(move |x| (x, y)))
+            .map(|(x, y)| kind(x, y))
+            .collect();
+        Self { map }
+    }
+
     pub fn kind_at(&self, x: f32, y: f32) -> Option<u8> {
         let ax = x + 0.5;
         let ay = y + 0.5;
@@ -39,48 +50,62 @@ impl HitMap {
         matches!(self.kind_at(x, y), Some(1) | None)
     }
 
-    pub fn check_left(&self, x: f32, y: f32) -> bool {
-        for yo in 0..16 {
-            if self.is_block(x, y + yo as f32) {
-                return true;
-            }
-        }
-        false
+    pub fn check_bottom(&self, x: f32, y: f32) -> bool {
+        self.check(Probe::Bottom, x, y)
     }
 
-    pub fn check_right(&self, x: f32, y: f32) -> bool {
-        for yo in 0..16 {
-            if self.is_block(x + 9.0, y + yo as f32) {
-                return true;
-            }
+    /// Positive tolerance forgives touching the edge of a deadly area by that many pixels,
+    /// negative one makes it lethal to come that close.
+    pub fn check_dead(&self, x: f32, y: f32, tolerance: i32) -> bool {
+        let spread = tolerance.abs();
+        let mut points =
+            (-spread..=spread).flat_map(|offset| Probe::Dead.points(x + offset as f32, y));
+        let dead = |point| self.probe_hits(Probe::Dead, point);
+        if tolerance > 0 {
+            points.all(dead)
+        } else {
+            points.any(dead)
         }
-        false
     }
 
-    pub fn check_top(&self, x: f32, y: f32) -> bool {
-        for xo in 0..10 {
-            if self.is_block(x + xo as f32, y) {
-                return true;
-            }
-        }
-        false
+    pub fn check(&self, probe: Probe, x: f32, y: f32) -> bool {
+        probe
+            .points(x, y)
+            .any(|point| self.probe_hits(probe, point))
     }
 
-    pub fn check_bottom(&self, x: f32, y: f32) -> bool {
-        for xo in 0..10 {
-            if self.is_block(x + xo as f32, y + 15.0) {
-                return true;
+    pub fn probe_hits(&self, probe: Probe, point: Vec2) -> bool {
+        match probe {
+            Probe::Left | Probe::Right | Probe::Top | Probe::Bottom => {
+                self.is_block(point.x, point.y)
             }
+            Probe::Dead => matches!(self.kind_at(point.x, point.y), Some(2)),
+            Probe::NextLevel => matches!(self.kind_at(point.x, point.y), Some(3)),
         }
-        false
     }
+}
 
-    pub fn check_dead(&self, x: f32, y: f32) -> bool {
-        matches!(self.kind_at(x + 5.0, y + 15.0), Some(2))
-    }
+/// Sensor of the player's 10x16 box, `x` and `y` are the top left corner of the box.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Probe {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Dead,
+    NextLevel,
+}
 
-    pub fn check_next_level(&self, x: f32, y: f32) -> bool {
-        matches!(self.kind_at(x + 5.0, y + 15.0), Some(3))
+impl Probe {
+    pub fn points(self, x: f32, y: f32) -> impl Iterator<Item = Vec2> {
+        let (start, step, count) = match self {
+            Probe::Left => (Vec2::new(x, y), Vec2::Y, 16),
+            Probe::Right => (Vec2::new(x + 9.0, y), Vec2::Y, 16),
+            Probe::Top => (Vec2::new(x, y), Vec2::X, 10),
+            Probe::Bottom => (Vec2::new(x, y + 15.0), Vec2::X, 10),
+            Probe::Dead | Probe::NextLevel => (Vec2::new(x + 5.0, y + 15.0), Vec2::ZERO, 1),
+        };
+        (0..count).map(move |index| start + step * index as f32)
     }
 }
 
@@ -102,3 +127,44 @@ impl AssetLoader for HitMapLoader {
         &["hit"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deadly columns from 1000 to 1010 on every row.
+    fn dead_map() -> HitMap {
+        HitMap::from_fn(|x, _| if (1000..=1010).contains(&x) { 2 } else { 0 })
+    }
+
+    /// Check of the player whose dead probe is at `probe_x`.
+    fn dead_at(hit_map: &HitMap, probe_x: f32, tolerance: i32) -> bool {
+        hit_map.check_dead(probe_x - 5.0, 50.0, tolerance)
+    }
+
+    #[test]
+    fn zero_tolerance_checks_one_point() {
+        let hit_map = dead_map();
+        assert!(dead_at(&hit_map, 1000.0, 0));
+        assert!(!dead_at(&hit_map, 999.0, 0));
+    }
+
+    #[test]
+    fn positive_tolerance_needs_the_whole_spread_dead() {
+        let hit_map = dead_map();
+        assert!(!dead_at(&hit_map, 1000.0, 2));
+        assert!(!dead_at(&hit_map, 1001.0, 2));
+        assert!(dead_at(&hit_map, 1002.0, 2));
+        assert!(dead_at(&hit_map, 1008.0, 2));
+        assert!(!dead_at(&hit_map, 1009.0, 2));
+    }
+
+    #[test]
+    fn negative_tolerance_kills_near_the_edge() {
+        let hit_map = dead_map();
+        assert!(dead_at(&hit_map, 998.0, -2));
+        assert!(!dead_at(&hit_map, 997.0, -2));
+        assert!(dead_at(&hit_map, 1012.0, -2));
+        assert!(!dead_at(&hit_map, 1013.0, -2));
+    }
+}
@@ -0,0 +1,443 @@
+use std::ops::BitOr;
+
+use quad::prelude::*;
+
+use crate::{
+    config::{Config, Controls},
+    window::is_alt_pressed,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Jump,
+    Pause,
+    Confirm,
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::Jump,
+        Action::Pause,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Jump => "jump",
+            Action::Pause => "pause",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|action| action.name() == name)
+    }
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+/// Set of actions active during a single frame.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct ActionSet(u16);
+
+impl ActionSet {
+    pub fn insert(&mut self, action: Action) {
+        self.0 |= action.bit();
+    }
+
+    pub fn remove(&mut self, action: Action) {
+        self.0 &= !action.bit();
+    }
+
+    pub fn contains(self, action: Action) -> bool {
+        self.0 & action.bit() != 0
+    }
+}
+
+impl FromIterator<Action> for ActionSet {
+    fn from_iter<I: IntoIterator<Item = Action>>(actions: I) -> Self {
+        let mut set = Self::default();
+        actions.into_iter().for_each(|action| set.insert(action));
+        set
+    }
+}
+
+impl BitOr for ActionSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+/// Actions of the current and the previous frame. Gameplay reads this instead of the keyboard,
+/// so any other source (replay, scripted input) can drive the game by calling `update`.
+#[derive(Resource, Default)]
+pub struct ActionState {
+    current: ActionSet,
+    previous: ActionSet,
+}
+
+impl ActionState {
+    pub fn update(&mut self, actions: ActionSet) {
+        self.previous = self.current;
+        self.current = actions;
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.current.contains(action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.current.contains(action) && !self.previous.contains(action)
+    }
+}
+
+/// State of the first frame the given actions are pressed.
+#[cfg(test)]
+pub fn actions(pressed: &[Action]) -> ActionState {
+    let mut actions = ActionState::default();
+    actions.update(pressed.iter().copied().collect());
+    actions
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bindings {
+    keys: [Vec<KeyCode>; Action::ALL.len()],
+}
+
+impl Bindings {
+    pub fn preset(controls: Controls) -> Self {
+        let mut bindings = Self {
+            keys: Default::default(),
+        };
+        bindings.set(Action::Pause, vec![KeyCode::Escape]);
+        bindings.set(Action::Confirm, vec![KeyCode::Enter]);
+        bindings.set(Action::Back, vec![KeyCode::Escape]);
+        match controls {
+            Controls::Arrows => {
+                bindings.set(Action::Left, vec![KeyCode::ArrowLeft]);
+                bindings.set(Action::Right, vec![KeyCode::ArrowRight]);
+                bindings.set(Action::Up, vec![KeyCode::ArrowUp]);
+                bindings.set(Action::Down, vec![KeyCode::ArrowDown]);
+                bindings.set(Action::Jump, vec![KeyCode::ArrowUp]);
+            }
+            Controls::Wasd => {
+                bindings.set(Action::Left, vec![KeyCode::KeyA, KeyCode::ArrowLeft]);
+                bindings.set(Action::Right, vec![KeyCode::KeyD, KeyCode::ArrowRight]);
+                bindings.set(Action::Up, vec![KeyCode::KeyW, KeyCode::ArrowUp]);
+                bindings.set(Action::Down, vec![KeyCode::KeyS, KeyCode::ArrowDown]);
+                bindings.set(Action::Jump, vec![KeyCode::Space, KeyCode::KeyW]);
+            }
+        }
+        bindings
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        &self.keys[action as usize]
+    }
+
+    pub fn set(&mut self, action: Action, keys: Vec<KeyCode>) {
+        self.keys[action as usize] = keys;
+    }
+
+    pub fn actions(&self, keyboard: &KeyboardInput) -> ActionSet {
+        let mut actions = ActionSet::default();
+        for action in Action::ALL {
+            if self.keys(action).iter().any(|&key| keyboard.pressed(key)) {
+                actions.insert(action);
+            }
+        }
+        actions
+    }
+}
+
+const STICK_DEAD_ZONE: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PadButton {
+    South,
+    East,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl PadButton {
+    const ALL: [PadButton; 7] = [
+        PadButton::South,
+        PadButton::East,
+        PadButton::Start,
+        PadButton::DPadUp,
+        PadButton::DPadDown,
+        PadButton::DPadLeft,
+        PadButton::DPadRight,
+    ];
+
+    fn button(self) -> GamepadButton {
+        match self {
+            PadButton::South => GamepadButton::South,
+            PadButton::East => GamepadButton::East,
+            PadButton::Start => GamepadButton::Start,
+            PadButton::DPadUp => GamepadButton::DPadUp,
+            PadButton::DPadDown => GamepadButton::DPadDown,
+            PadButton::DPadLeft => GamepadButton::DPadLeft,
+            PadButton::DPadRight => GamepadButton::DPadRight,
+        }
+    }
+
+    fn actions(self) -> &'static [Action] {
+        match self {
+            PadButton::South => &[Action::Jump, Action::Confirm],
+            PadButton::East => &[Action::Back],
+            PadButton::Start => &[Action::Pause, Action::Confirm],
+            PadButton::DPadUp => &[Action::Up],
+            PadButton::DPadDown => &[Action::Down],
+            PadButton::DPadLeft => &[Action::Left],
+            PadButton::DPadRight => &[Action::Right],
+        }
+    }
+}
+
+/// Snapshot of the connected gamepads merged into one, stick Y axis points up.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct PadState {
+    buttons: u8,
+    pub stick: Vec2,
+}
+
+impl PadState {
+    pub fn from_gamepads(gamepads: &GamepadInput) -> Self {
+        let mut pad = Self::default();
+        for button in PadButton::ALL {
+            if gamepads.pressed(button.button()) {
+                pad.press(button);
+            }
+        }
+        pad.stick = Vec2::new(
+            gamepads.axis(GamepadAxis::LeftStickX),
+            gamepads.axis(GamepadAxis::LeftStickY),
+        );
+        pad
+    }
+
+    pub fn press(&mut self, button: PadButton) {
+        self.buttons |= 1 << button as u8;
+    }
+
+    pub fn pressed(&self, button: PadButton) -> bool {
+        self.buttons & (1 << button as u8) != 0
+    }
+
+    pub fn actions(&self) -> ActionSet {
+        let mut actions = ActionSet::default();
+        for button in PadButton::ALL {
+            if self.pressed(button) {
+                button
+                    .actions()
+                    .iter()
+                    .for_each(|&action| actions.insert(action));
+            }
+        }
+
+        if self.stick.x <= -STICK_DEAD_ZONE {
+            actions.insert(Action::Left);
+        } else if self.stick.x >= STICK_DEAD_ZONE {
+            actions.insert(Action::Right);
+        }
+        if self.stick.y >= STICK_DEAD_ZONE {
+            actions.insert(Action::Up);
+        } else if self.stick.y <= -STICK_DEAD_ZONE {
+            actions.insert(Action::Down);
+        }
+        actions
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 54] = [
+    ("A", KeyCode::KeyA),
+    ("B", KeyCode::KeyB),
+    ("C", KeyCode::KeyC),
+    ("D", KeyCode::KeyD),
+    ("E", KeyCode::KeyE),
+    ("F", KeyCode::KeyF),
+    ("G", KeyCode::KeyG),
+    ("H", KeyCode::KeyH),
+    ("I", KeyCode::KeyI),
+    ("J", KeyCode::KeyJ),
+    ("K", KeyCode::KeyK),
+    ("L", KeyCode::KeyL),
+    ("M", KeyCode::KeyM),
+    ("N", KeyCode::KeyN),
+    ("O", KeyCode::KeyO),
+    ("P", KeyCode::KeyP),
+    ("Q", KeyCode::KeyQ),
+    ("R", KeyCode::KeyR),
+    ("S", KeyCode::KeyS),
+    ("T", KeyCode::KeyT),
+    ("U", KeyCode::KeyU),
+    ("V", KeyCode::KeyV),
+    ("W", KeyCode::KeyW),
+    ("X", KeyCode::KeyX),
+    ("Y", KeyCode::KeyY),
+    ("Z", KeyCode::KeyZ),
+    ("0", KeyCode::Digit0),
+    ("1", KeyCode::Digit1),
+    ("2", KeyCode::Digit2),
+    ("3", KeyCode::Digit3),
+    ("4", KeyCode::Digit4),
+    ("5", KeyCode::Digit5),
+    ("6", KeyCode::Digit6),
+    ("7", KeyCode::Digit7),
+    ("8", KeyCode::Digit8),
+    ("9", KeyCode::Digit9),
+    ("Left", KeyCode::ArrowLeft),
+    ("Right", KeyCode::ArrowRight),
+    ("Up", KeyCode::ArrowUp),
+    ("Down", KeyCode::ArrowDown),
+    ("Space", KeyCode::Space),
+    ("Enter", KeyCode::Enter),
+    ("Escape", KeyCode::Escape),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("LeftShift", KeyCode::ShiftLeft),
+    ("RightShift", KeyCode::ShiftRight),
+    ("LeftControl", KeyCode::ControlLeft),
+    ("RightControl", KeyCode::ControlRight),
+    ("LeftAlt", KeyCode::AltLeft),
+    ("RightAlt", KeyCode::AltRight),
+    ("Insert", KeyCode::Insert),
+    ("Delete", KeyCode::Delete),
+    ("Home", KeyCode::Home),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, code)| *code == key)
+        .map(|(name, _)| *name)
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, code)| *code)
+}
+
+pub fn update_actions(
+    keyboard: Res<KeyboardInput>,
+    gamepads: Res<GamepadInput>,
+    config: Res<Config>,
+    mut actions: ResMut<ActionState>,
+) {
+    let pad = PadState::from_gamepads(&gamepads);
+    let mut keys = config.bindings.actions(&keyboard);
+    // Alt+Enter toggles fullscreen
+    if is_alt_pressed(&keyboard) {
+        keys.remove(Action::Confirm);
+    }
+    actions.update(keys | pad.actions());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Choice;
+
+    #[test]
+    fn stick_inside_dead_zone_is_ignored() {
+        let pad = PadState {
+            stick: Vec2::new(STICK_DEAD_ZONE - 0.01, -(STICK_DEAD_ZONE - 0.01)),
+            ..Default::default()
+        };
+        assert_eq!(pad.actions(), ActionSet::default());
+    }
+
+    #[test]
+    fn stick_outside_dead_zone_moves() {
+        let pad = PadState {
+            stick: Vec2::new(-STICK_DEAD_ZONE, STICK_DEAD_ZONE),
+            ..Default::default()
+        };
+        assert_eq!(
+            pad.actions(),
+            ActionSet::from_iter([Action::Left, Action::Up])
+        );
+
+        let pad = PadState {
+            stick: Vec2::new(1.0, -1.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            pad.actions(),
+            ActionSet::from_iter([Action::Right, Action::Down])
+        );
+    }
+
+    #[test]
+    fn start_pauses_and_confirms() {
+        let mut pad = PadState::default();
+        pad.press(PadButton::Start);
+        assert_eq!(
+            pad.actions(),
+            ActionSet::from_iter([Action::Pause, Action::Confirm])
+        );
+    }
+
+    #[test]
+    fn south_jumps_and_confirms() {
+        let mut pad = PadState::default();
+        pad.press(PadButton::South);
+        assert_eq!(
+            pad.actions(),
+            ActionSet::from_iter([Action::Jump, Action::Confirm])
+        );
+    }
+
+    #[test]
+    fn just_pressed_only_on_first_frame() {
+        let mut state = actions(&[Action::Jump]);
+        assert!(state.just_pressed(Action::Jump));
+        assert!(state.pressed(Action::Jump));
+
+        state.update(ActionSet::from_iter([Action::Jump]));
+        assert!(!state.just_pressed(Action::Jump));
+        assert!(state.pressed(Action::Jump));
+
+        state.update(ActionSet::default());
+        state.update(ActionSet::from_iter([Action::Jump]));
+        assert!(state.just_pressed(Action::Jump));
+    }
+
+    #[test]
+    fn arrows_change_values_in_every_preset() {
+        for &controls in Controls::ALL {
+            let bindings = Bindings::preset(controls);
+            assert!(bindings.keys(Action::Left).contains(&KeyCode::ArrowLeft));
+            assert!(bindings.keys(Action::Right).contains(&KeyCode::ArrowRight));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::bail;
+
+/// File in the temporary directory unique to the test process.
+#[cfg(test)]
+pub fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("mouse1rs-{}-{name}", std::process::id()))
+}
+
+pub fn parse(text: &str) -> anyhow::Result<Vec<(&str, &str)>> {
+    let mut pairs = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => pairs.push((key.trim(), value.trim())),
+            None => bail!("Invalid line {}: {}", index + 1, line),
+        }
+    }
+    Ok(pairs)
+}
+
+pub fn parse_list<T: std::str::FromStr>(value: &str) -> anyhow::Result<Vec<T>> {
+    let mut list = Vec::new();
+    for item in value.split(',') {
+        match item.trim().parse() {
+            Ok(item) => list.push(item),
+            Err(_) => bail!("Invalid value: {}", item.trim()),
+        }
+    }
+    Ok(list)
+}
@@ -3,22 +3,38 @@ use std::path::PathBuf;
 use quad::prelude::*;
 
 use crate::{
+    animation::PlayerAnimation,
+    audio::{Audio, Sound},
+    camera::CameraRig,
+    cheats::Cheats,
+    config::{Config, Filter, Physics, Scaling},
     constant::*,
+    difficulty::Difficulty,
+    filter::Palette,
     game_complete::GameCompleteScene,
     hit_map::HitMap,
+    input::{Action, ActionState, update_actions},
+    level_info::LevelInfo,
     level_opening::LevelOpeningScene,
     lost_life::LostLifeScene,
     mouse::{GameAssets, Lifes},
-    player::{Player, PlayerOrientation, PlayerState},
+    pause::{PauseAction, PauseScene},
+    player::{Player, PlayerState},
+    progress::{PROGRESS_PATH, Progress},
+    transition::{TransitionEffect, TransitionScene},
+    window::update_window,
 };
 
 #[derive(Resource)]
 pub struct Level(pub usize);
 
 pub struct LevelAssets {
-    pub background: Handle<Image>,
+    /// Parallax layer images in the order of the level info, loaded after it.
+    pub layers: Vec<Handle<Image>>,
     pub foreground: Handle<Image>,
     pub hit_map: Handle<HitMap>,
+    pub info: Handle<LevelInfo>,
+    pub thumbnail: Handle<Image>,
 }
 
 impl LevelAssets {
@@ -26,42 +42,102 @@ impl LevelAssets {
         format!("levels/{level}/fg.tga").into()
     }
 
-    pub fn background_path(level: u32) -> PathBuf {
-        format!("levels/{level}/bcg.tga").into()
+    pub fn layer_path(level: u32, image: &str) -> PathBuf {
+        format!("levels/{level}/{image}").into()
     }
 
     pub fn hit_map_path(level: u32) -> PathBuf {
         format!("levels/{level}/map.hit").into()
     }
+
+    pub fn info_path(level: u32) -> PathBuf {
+        format!("levels/{level}/level.lvl").into()
+    }
 }
 
 #[derive(Component)]
-pub struct BackgroundImage;
+pub struct Layer {
+    scroll: f32,
+}
+
+impl Layer {
+    fn translation_x(&self, camera_position: f32) -> f32 {
+        camera_position * (1.0 - self.scroll)
+    }
+}
 
 #[derive(Component)]
 pub struct SceneRoot;
 
+/// Flat color of a sprite, limited to the original palette by the palette filter.
+#[derive(Component)]
+struct Tint(Color);
+
+/// Scanline or vignette overlay over the view.
+#[derive(Component)]
+struct FilterOverlay;
+
+/// Levels are played from right to left, a checkpoint is passed once the player gets to its left.
+/// The flag is dimmed until the checkpoint becomes the respawn point.
+#[derive(Component)]
+pub struct Checkpoint {
+    index: usize,
+    position: Vec2,
+}
+
+impl Checkpoint {
+    fn is_passed(&self, player: &Player) -> bool {
+        player.position.x <= self.position.x
+    }
+}
+
+#[derive(Component)]
+struct CheckpointFlag {
+    index: usize,
+}
+
+#[derive(Resource)]
+pub struct RespawnPoint {
+    checkpoint: usize,
+    position: Vec2,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 enum LevelState {
     Play,
+    Pause,
+    Restart,
     Quit,
+    /// Freeze frame followed by the death clip, ends in Dead.
+    Dying,
     Dead,
+    /// The player walks out and fades away, ends in Next.
+    Exiting,
     Next,
+    #[cfg(feature = "console")]
+    Console,
 }
 
 #[derive(Resource)]
 struct LevelData {
     state: LevelState,
-    camera_position: f32,
+    camera: CameraRig,
     camera_max: f32,
     camera_min: f32,
     root: Entity,
     zoom: f32,
+    /// Physics required by the level, otherwise the one from the settings is used.
+    physics: Option<Physics>,
+    lethal_fall: Option<f32>,
+    /// Time since the death or exit sequence started.
+    timeline: f32,
 }
 
 pub struct LevelSchedule {
     start: Schedule<(), SceneResult>,
     update: Schedule<(), SceneResult>,
+    pause: Schedule<(), SceneResult>,
+    resume: Schedule<(), SceneResult>,
 }
 
 #[derive(Default)]
@@ -76,12 +152,10 @@ impl Scene for LevelScene {
                 .add(level_start)
                 .add(finalize_start)
                 .build(),
-            update: Scheduler::chain(world)
-                .add(update_player)
-                .add(handle_input)
-                .add(update_zoom)
-                .add(position_camera)
-                .add(position_background)
+            update: level_update_schedule(world),
+            pause: Scheduler::single(level_pause),
+            resume: Scheduler::chain(world)
+                .add(level_resume)
                 .add(finalize_update)
                 .build(),
         });
@@ -89,64 +163,148 @@ impl Scene for LevelScene {
         match stage {
             SceneStage::Start => schedule.start.run(world),
             SceneStage::Update => schedule.update.run(world),
+            SceneStage::Pause => schedule.pause.run(world),
+            SceneStage::Resume => schedule.resume.run(world),
             _ => unreachable!(),
         }
     }
 }
 
+fn level_update_schedule(world: &mut World) -> Schedule<(), SceneResult> {
+    let chain = Scheduler::chain(world)
+        .add(update_actions)
+        .add(update_window)
+        .add(update_player);
+    // Debug tooling is only compiled into builds with the console
+    #[cfg(feature = "console")]
+    let chain = chain
+        .add(crate::debug_overlay::update_debug_overlay)
+        .add(crate::debug_overlay::update_probe_points);
+    chain
+        .add(activate_checkpoints)
+        .add(handle_input)
+        .add(update_zoom)
+        .add(position_camera)
+        .add(position_layers)
+        .add(apply_palette)
+        .add(apply_filter)
+        .add(finalize_update)
+        .build()
+}
+
+#[allow(clippy::too_many_arguments)]
 fn level_start(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
     level: Res<Level>,
+    respawn_point: Option<Res<RespawnPoint>>,
+    config: Res<Config>,
     windows: Res<Windows>,
     hit_map_assets: Res<Assets<HitMap>>,
+    level_info_assets: Res<Assets<LevelInfo>>,
+    animations: Res<Assets<PlayerAnimation>>,
+    difficulty: Res<Difficulty>,
+    mut audio: ResMut<Audio>,
     mut camera: Query<(&Camera2d, &mut Transform)>,
 ) {
     let window_size = windows.primary().size();
     let hit_map = hit_map_assets
         .get(&game_assets.level[level.0].hit_map)
         .unwrap();
+    let level_info = level_info_assets
+        .get(&game_assets.level[level.0].info)
+        .unwrap();
 
-    let player_position = Vec2::new(3180.0, 50.0);
-    let player = Player {
-        orientation: PlayerOrientation::Left,
-        state: if hit_map.check_bottom(player_position.x, player_position.y + 1.0) {
-            PlayerState::Standing
-        } else {
-            PlayerState::Falling
-        },
-        position: player_position,
-        jump_phase: 0.0,
-        animation_phase: 0.0,
-    };
+    audio.play_music(level_info.music.as_deref());
 
-    let (zoom, camera_min, camera_max) = camera_properties(window_size);
-    let camera_position = camera_max;
+    let player_position = respawn_point
+        .as_ref()
+        .map_or(Vec2::new(3180.0, 50.0), |point| point.position);
+    let mut player = Player::new(player_position);
+    player.speed = difficulty.speed();
+    if !hit_map.check_bottom(player_position.x, player_position.y + 1.0) {
+        player.state = PlayerState::Falling;
+    }
+    let animation = animations.get(&game_assets.player_animation).unwrap();
+    let (index, flip_x) = animation.sprite(&player, false);
+
+    let (zoom, camera_min, camera_max) = camera_properties(window_size, config.scaling);
+    let mut camera_rig = CameraRig::new(
+        player.position.x + PLAYER_X_OFFSET,
+        player.orientation.direction(),
+        camera_min,
+        camera_max,
+    );
+    if let Some(dead_zone) = level_info.camera_dead_zone {
+        camera_rig.dead_zone = dead_zone;
+    }
+    if let Some(look_ahead) = level_info.camera_look_ahead {
+        camera_rig.look_ahead = look_ahead;
+    }
+    if let Some(smooth_time) = level_info.camera_smooth_time {
+        camera_rig.smooth_time = smooth_time;
+    }
+    if respawn_point.is_none()
+        && let Some((x, hold)) = level_info.intro_pan
+    {
+        camera_rig.pan_to(x + PLAYER_X_OFFSET, hold);
+    }
+    let camera_position = camera_rig.position();
+
+    let layers: Vec<Entity> = level_info
+        .layers
+        .iter()
+        .zip(&game_assets.level[level.0].layers)
+        .map(|(info, image)| {
+            let layer = Layer {
+                scroll: info.scroll,
+            };
+            // Wide enough to cover the screen over the whole scrolled range
+            let rect = info.repeat.then(|| Rect {
+                min: Vec2::new(0.0, 0.0),
+                max: Vec2::new(
+                    TOTAL_SCREEN_WIDTH * info.scroll.abs() + SCREEN_WIDTH,
+                    SCREEN_HEIGHT,
+                ),
+            });
+            commands
+                .spawn()
+                .insert_bundle(SpriteBundle {
+                    texture: image.clone(),
+                    sprite: Sprite {
+                        rect,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(
+                        layer.translation_x(camera_position),
+                        -info.offset_y,
+                        info.z,
+                    ),
+                    ..Default::default()
+                })
+                .insert(layer)
+                .id()
+        })
+        .collect();
 
-    let background = commands
+    let foreground = commands
         .spawn()
-        .insert(BackgroundImage)
         .insert_bundle(SpriteBundle {
-            texture: game_assets.level[level.0].background.clone(),
-            sprite: Sprite {
-                rect: Some(Rect {
-                    min: Vec2::new(0.0, 0.0),
-                    max: Vec2::new(BCG_SCREEN_COUNT * SCREEN_WIDTH, SCREEN_HEIGHT),
-                }),
-                ..Default::default()
-            },
-            transform: Transform::from_xy(camera_position / 2.0, 0.0),
+            texture: game_assets.level[level.0].foreground.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
             ..Default::default()
         })
         .id();
 
-    let foreground = commands
+    // Shown and sized by apply_filter
+    let filter_overlay = commands
         .spawn()
         .insert_bundle(SpriteBundle {
-            texture: game_assets.level[level.0].foreground.clone(),
-            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            transform: Transform::from_xyz(0.0, 0.0, 9.0),
+            visibility: Visibility::Hidden,
             ..Default::default()
         })
+        .insert(FilterOverlay)
         .id();
 
     let player = commands
@@ -154,7 +312,8 @@ fn level_start(
         .insert_bundle(SpriteSheetBundle {
             texture_atlas: game_assets.player.clone(),
             sprite: TextureAtlasSprite {
-                index: player.sprite_index(),
+                index,
+                flip_x,
                 ..Default::default()
             },
             transform: Transform::from_xyz(
@@ -167,9 +326,26 @@ fn level_start(
         .insert(player)
         .id();
 
+    let checkpoints: &[Vec2] = if difficulty.has_checkpoints() {
+        &level_info.checkpoints
+    } else {
+        &[]
+    };
+    let active_checkpoint = respawn_point.as_ref().map(|point| point.checkpoint);
+    let checkpoints: Vec<Entity> = checkpoints
+        .iter()
+        .enumerate()
+        .map(|(index, &position)| {
+            let active = active_checkpoint == Some(index);
+            spawn_checkpoint(&mut commands, Checkpoint { index, position }, active)
+        })
+        .collect();
+
     let root = commands
         .spawn()
-        .push_children(&[foreground, background, player])
+        .push_children(&[foreground, player, filter_overlay])
+        .push_children(&layers)
+        .push_children(&checkpoints)
         .insert(SceneRoot)
         .insert_bundle(SpatialBundle {
             transform: Transform {
@@ -183,11 +359,14 @@ fn level_start(
 
     commands.insert_resource(LevelData {
         state: LevelState::Play,
-        camera_position,
+        camera: camera_rig,
         camera_min,
         camera_max,
         root,
         zoom,
+        physics: level_info.physics,
+        lethal_fall: level_info.lethal_fall.or(difficulty.lethal_fall()),
+        timeline: 0.0,
     });
 
     if let Ok((_, mut camera_pos)) = camera.get_single_mut() {
@@ -199,13 +378,19 @@ fn finalize_start() -> SceneResult {
     SceneResult::Ok(SceneStage::Update)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn update_player(
     time: Res<Time>,
-    keyboard: Res<KeyboardInput>,
+    config: Res<Config>,
+    actions: Res<ActionState>,
+    cheats: Res<Cheats>,
+    difficulty: Res<Difficulty>,
     game_assets: Res<GameAssets>,
     level: Res<Level>,
     mut level_data: ResMut<LevelData>,
+    mut audio: ResMut<Audio>,
     hit_map_assets: Res<Assets<HitMap>>,
+    animations: Res<Assets<PlayerAnimation>>,
     mut player_query: Query<(&mut Player, &mut Transform, &mut TextureAtlasSprite)>,
 ) {
     let (mut player, mut transform, mut sprite) = player_query.single_mut();
@@ -213,41 +398,210 @@ fn update_player(
         .get(&game_assets.level[level.0].hit_map)
         .unwrap();
 
-    if player.state == PlayerState::Standing && player.can_fall(hit_map) {
-        player.state = PlayerState::Falling;
+    #[cfg(feature = "console")]
+    player.probes.clear();
+    let dt = time.delta_seconds();
+    let previous_state = player.state;
+    let previous_level_state = level_data.state;
+    let previous_phase = player.animation_phase;
+    match level_data.state {
+        LevelState::Dying => {
+            level_data.timeline += dt;
+            if level_data.timeline < DEATH_FREEZE_TIME {
+                return;
+            }
+            player.state = PlayerState::Dying;
+            if level_data.timeline >= DEATH_FREEZE_TIME + DEATH_ANIMATION_TIME {
+                level_data.state = LevelState::Dead;
+            }
+        }
+        LevelState::Exiting => {
+            level_data.timeline += dt;
+            player.walk_out(dt);
+            sprite
+                .color
+                .set_a((1.0 - level_data.timeline / EXIT_TIME).max(0.0));
+            if level_data.timeline >= EXIT_TIME {
+                level_data.state = LevelState::Next;
+            }
+        }
+        _ if player.state == PlayerState::Squashed => {
+            if player.update_squash(dt) {
+                level_data.state = LevelState::Dead;
+            }
+        }
+        _ if cheats.noclip => player.fly(actions.as_ref(), dt),
+        _ => {
+            // The settings can change while the level is paused
+            match level_data.physics.unwrap_or(config.physics) {
+                Physics::Classic => player.update_classic(&actions, dt, hit_map),
+                Physics::Momentum => player.update_momentum(&actions, dt, hit_map),
+            }
+            let fall = player.track_fall();
+            let lethal = level_data.lethal_fall.is_some_and(|height| fall > height);
+            if lethal && !cheats.god {
+                level_data
+                    .camera
+                    .shake(CAMERA_SHAKE_AMPLITUDE, CAMERA_SHAKE_TIME);
+                player.squash();
+            } else if player.state == PlayerState::Standing {
+                if player.is_dead(hit_map, difficulty.dead_tolerance()) && !cheats.god {
+                    level_data
+                        .camera
+                        .shake(CAMERA_SHAKE_AMPLITUDE, CAMERA_SHAKE_TIME);
+                    level_data.state = LevelState::Dying;
+                    level_data.timeline = 0.0;
+                } else if player.is_next_level(hit_map) {
+                    level_data.state = LevelState::Exiting;
+                    level_data.timeline = 0.0;
+                }
+            }
+        }
     }
 
-    if keyboard.pressed(KeyCode::ArrowUp) {
-        player.jump(hit_map);
+    if let Some(sound) = update_sound(
+        (previous_level_state, previous_state),
+        (level_data.state, player.state),
+    ) {
+        audio.play(sound);
     }
-    if keyboard.pressed(KeyCode::ArrowLeft) {
-        player.move_left(time.as_ref(), hit_map)
-    } else if keyboard.pressed(KeyCode::ArrowRight) {
-        player.move_right(time.as_ref(), hit_map)
+
+    // The squash clip is flattened onto the floor
+    let squash = if player.state == PlayerState::Squashed {
+        0.5
+    } else {
+        1.0
+    };
+    transform.translation.x = player.position.x + PLAYER_X_OFFSET;
+    transform.translation.y =
+        PLAYER_Y_OFFSET - player.position.y - PLAYER_HEIGHT * (1.0 - squash) / 2.0;
+    transform.scale.y = squash;
+
+    if player.state == previous_state {
+        player.animation_time += dt;
+    } else {
+        player.animation_time = 0.0;
     }
+    let walking = player.animation_phase != previous_phase;
+    let animation = animations.get(&game_assets.player_animation).unwrap();
+    (sprite.index, sprite.flip_x) = animation.sprite(&player, walking);
+}
 
-    if player.state == PlayerState::Jumping {
-        player.move_up(time.as_ref(), hit_map);
+/// Sound of the change made by one update of the player, the states are of the level and the player.
+fn update_sound(
+    previous: (LevelState, PlayerState),
+    current: (LevelState, PlayerState),
+) -> Option<Sound> {
+    match (previous, current) {
+        ((LevelState::Play, _), (LevelState::Dying, _)) => Some(Sound::Death),
+        ((LevelState::Play, _), (LevelState::Exiting, _)) => Some(Sound::Exit),
+        (
+            (_, PlayerState::Standing | PlayerState::Jumping | PlayerState::Falling),
+            (_, PlayerState::Squashed),
+        ) => Some(Sound::Death),
+        ((_, PlayerState::Standing | PlayerState::Falling), (_, PlayerState::Jumping)) => {
+            Some(Sound::Jump)
+        }
+        ((_, PlayerState::Jumping | PlayerState::Falling), (_, PlayerState::Standing)) => {
+            Some(Sound::Land)
+        }
+        _ => None,
     }
-    if player.state == PlayerState::Falling {
-        player.move_down(time.as_ref(), hit_map);
+}
+
+fn checkpoint_flag_color(active: bool) -> Color {
+    if active {
+        Color::rgb(0.9, 0.2, 0.1)
+    } else {
+        Color::rgb(0.4, 0.3, 0.3)
     }
-    if player.state == PlayerState::Standing {
-        if player.is_dead(hit_map) {
-            level_data.state = LevelState::Dead;
-        } else if player.is_next_level(hit_map) {
-            level_data.state = LevelState::Next;
-        }
+}
+
+/// Pole with a flag at the feet of the player standing on the checkpoint.
+/// The sprites are colored from their tints by apply_palette.
+fn spawn_checkpoint(commands: &mut Commands, checkpoint: Checkpoint, active: bool) -> Entity {
+    let flag = commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: checkpoint_flag_color(active),
+                custom_size: Some(Vec2::new(6.0, 4.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(3.5, 6.0, 0.0),
+            ..Default::default()
+        })
+        .insert(Tint(checkpoint_flag_color(active)))
+        .insert(CheckpointFlag {
+            index: checkpoint.index,
+        })
+        .id();
+    let pole_color = Color::rgb(0.3, 0.3, 0.3);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: pole_color,
+                custom_size: Some(Vec2::new(1.0, PLAYER_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(
+                checkpoint.position.x + PLAYER_X_OFFSET,
+                PLAYER_Y_OFFSET - checkpoint.position.y,
+                1.5,
+            ),
+            ..Default::default()
+        })
+        .insert(checkpoint)
+        .insert(Tint(pole_color))
+        .add_child(flag)
+        .id()
+}
+
+fn activate_checkpoints(
+    mut commands: Commands,
+    level_data: Res<LevelData>,
+    respawn_point: Option<Res<RespawnPoint>>,
+    player_query: Query<&Player>,
+    checkpoints: Query<&Checkpoint>,
+    mut flags: Query<(&CheckpointFlag, &mut Tint)>,
+) {
+    let player = player_query.single();
+    if level_data.state != LevelState::Play || player.state != PlayerState::Standing {
+        return;
     }
 
-    transform.translation.x = player.position.x + PLAYER_X_OFFSET;
-    transform.translation.y = PLAYER_Y_OFFSET - player.position.y;
-    sprite.index = player.sprite_index();
+    let last_checkpoint = respawn_point.map(|point| point.checkpoint);
+    let passed = checkpoints
+        .iter()
+        .filter(|checkpoint| checkpoint.is_passed(player))
+        .max_by_key(|checkpoint| checkpoint.index)
+        .filter(|checkpoint| last_checkpoint.is_none_or(|last| last < checkpoint.index));
+    if let Some(checkpoint) = passed {
+        commands.insert_resource(RespawnPoint {
+            checkpoint: checkpoint.index,
+            position: checkpoint.position,
+        });
+        for (flag, mut tint) in flags.iter_mut() {
+            tint.0 = checkpoint_flag_color(flag.index == checkpoint.index);
+        }
+    }
 }
 
-fn handle_input(mut level_data: ResMut<LevelData>, keyboard: Res<KeyboardInput>) {
-    if keyboard.just_pressed(KeyCode::Escape) {
-        level_data.state = LevelState::Quit;
+fn handle_input(
+    mut level_data: ResMut<LevelData>,
+    #[cfg(feature = "console")] mut cheats: ResMut<Cheats>,
+    actions: Res<ActionState>,
+    keyboard: Res<KeyboardInput>,
+) {
+    #[cfg(feature = "console")]
+    if keyboard.just_pressed(KeyCode::F3) {
+        cheats.hit_map = !cheats.hit_map;
+    }
+    // The death and exit sequences can't be interrupted
+    if level_data.state != LevelState::Play {
+        return;
+    }
+    if actions.just_pressed(Action::Pause) {
+        level_data.state = LevelState::Pause;
     }
     if keyboard.pressed(KeyCode::KeyM)
         && keyboard.pressed(KeyCode::KeyY)
@@ -255,19 +609,23 @@ fn handle_input(mut level_data: ResMut<LevelData>, keyboard: Res<KeyboardInput>)
     {
         level_data.state = LevelState::Next;
     }
+    #[cfg(feature = "console")]
+    if crate::console::is_toggled(&keyboard) {
+        level_data.state = LevelState::Console;
+    }
 }
 
 fn update_zoom(
+    config: Res<Config>,
     windows: Res<Windows>,
     mut level_data: ResMut<LevelData>,
     mut root: Query<(&SceneRoot, &mut Transform)>,
 ) {
     let window_size = windows.primary().size();
-    let (zoom, camera_min, camera_max) = camera_properties(window_size);
+    let (zoom, camera_min, camera_max) = camera_properties(window_size, config.scaling);
 
     level_data.camera_min = camera_min;
     level_data.camera_max = camera_max;
-    level_data.camera_position = level_data.camera_position.clamp(camera_min, camera_max);
     level_data.zoom = zoom;
 
     if let Ok((_, mut root_pos)) = root.get_single_mut() {
@@ -277,71 +635,326 @@ fn update_zoom(
 }
 
 fn position_camera(
+    time: Res<Time>,
     mut level_data: ResMut<LevelData>,
     player_query: Query<&Player>,
     mut camera_query: Query<(&Camera2d, &mut Transform)>,
 ) {
     let player = player_query.single();
-    let player_x = player.position.x + PLAYER_X_OFFSET;
-    level_data.camera_position = level_data
-        .camera_position
-        .clamp(player_x - 40.0, player_x + 40.0)
-        .clamp(level_data.camera_min, level_data.camera_max);
-
+    let (min, max) = (level_data.camera_min, level_data.camera_max);
+    let position = level_data.camera.update(
+        player.position.x + PLAYER_X_OFFSET,
+        player.orientation.direction(),
+        time.delta_seconds(),
+        min,
+        max,
+    );
+
+    let shake = level_data.camera.shake_offset();
     let (_, mut camera_pos) = camera_query.single_mut();
-    camera_pos.translation.x = level_data.camera_position * level_data.zoom;
+    camera_pos.translation.x = (position + shake.x) * level_data.zoom;
+    camera_pos.translation.y = shake.y * level_data.zoom;
+}
+
+fn position_layers(level_data: Res<LevelData>, mut layers: Query<(&Layer, &mut Transform)>) {
+    for (layer, mut layer_pos) in layers.iter_mut() {
+        layer_pos.translation.x = layer.translation_x(level_data.camera.position());
+    }
+}
+
+/// The level art is drawn in the original palette already, only the player sheet and the flat
+/// sprite colors can leave it.
+#[allow(clippy::too_many_arguments)]
+fn apply_palette(
+    config: Res<Config>,
+    palettes: Res<Assets<Palette>>,
+    animations: Res<Assets<PlayerAnimation>>,
+    mut game_assets: ResMut<GameAssets>,
+    mut images: ResMut<Assets<Image>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut player_query: Query<&mut Handle<TextureAtlas>, With<Player>>,
+    mut tints: Query<(&Tint, &mut Sprite)>,
+) {
+    let palette =
+        (config.filter == Filter::Palette).then(|| palettes.get(&game_assets.palette).unwrap());
+
+    if let Some(palette) = palette
+        && game_assets.palette_player == Handle::default()
+    {
+        let atlas = texture_atlases.get(&game_assets.player).unwrap();
+        let sheet = palette.quantize_image(images.get(&atlas.texture).unwrap());
+        let animation = animations.get(&game_assets.player_animation).unwrap();
+        game_assets.palette_player = texture_atlases.add(animation.atlas(images.add(sheet)));
+    }
+    let wanted = if palette.is_some() {
+        &game_assets.palette_player
+    } else {
+        &game_assets.player
+    };
+    let mut atlas = player_query.single_mut();
+    if *atlas != *wanted {
+        *atlas = wanted.clone();
+    }
+
+    for (tint, mut sprite) in tints.iter_mut() {
+        sprite.color = palette.map_or(tint.0, |palette| palette.nearest_color(tint.0));
+    }
 }
 
-fn position_background(
+fn apply_filter(
+    config: Res<Config>,
+    game_assets: Res<GameAssets>,
     level_data: Res<LevelData>,
-    mut background: Query<(&BackgroundImage, &mut Transform)>,
+    camera_query: Query<(&Camera2d, &Transform), Without<FilterOverlay>>,
+    mut overlay: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<FilterOverlay>,
+    >,
 ) {
-    if let Ok((_, mut background_pos)) = background.get_single_mut() {
-        background_pos.translation.x = level_data.camera_position / 2.0;
+    let (mut texture, mut sprite, mut transform, mut visibility) = overlay.single_mut();
+    let wanted = match config.filter {
+        Filter::Scanlines => &game_assets.scanlines,
+        Filter::Vignette => &game_assets.vignette,
+        Filter::None | Filter::Palette => {
+            *visibility = Visibility::Hidden;
+            return;
+        }
+    };
+    if *texture != *wanted {
+        *texture = wanted.clone();
+    }
+    *visibility = Visibility::Inherited;
+
+    // Covers the visible part of the level
+    let (_, camera_pos) = camera_query.single();
+    let view_width = TOTAL_SCREEN_WIDTH - (level_data.camera_max - level_data.camera_min);
+    sprite.custom_size = Some(Vec2::new(view_width, SCREEN_HEIGHT));
+    transform.translation.x = camera_pos.translation.x / level_data.zoom;
+    transform.translation.y = camera_pos.translation.y / level_data.zoom;
+}
+
+fn level_pause(mut level_data: ResMut<LevelData>) -> SceneResult {
+    let state = std::mem::replace(&mut level_data.state, LevelState::Play);
+    match state {
+        #[cfg(feature = "console")]
+        LevelState::Console => SceneResult::Push(
+            Box::<crate::console::ConsoleScene>::default(),
+            SceneStage::Start,
+        ),
+        _ => SceneResult::Push(Box::<PauseScene>::default(), SceneStage::Start),
     }
 }
 
+fn level_resume(
+    mut commands: Commands,
+    mut level_data: ResMut<LevelData>,
+    action: Res<PauseAction>,
+) {
+    level_data.state = match *action {
+        PauseAction::Resume => LevelState::Play,
+        PauseAction::Restart => LevelState::Restart,
+        PauseAction::Quit => LevelState::Quit,
+    };
+    commands.remove_resource::<PauseAction>();
+}
+
 fn finalize_update(
     mut commands: Commands,
     level_data: ResMut<LevelData>,
     mut level: ResMut<Level>,
     mut lifes: ResMut<Lifes>,
+    mut progress: ResMut<Progress>,
 ) -> SceneResult {
     match level_data.state {
+        LevelState::Pause => SceneResult::Ok(SceneStage::Pause),
+        #[cfg(feature = "console")]
+        LevelState::Console => SceneResult::Ok(SceneStage::Pause),
+        LevelState::Restart => {
+            commands.remove_resource::<LevelData>();
+            commands.remove_resource::<RespawnPoint>();
+            SceneResult::Replace(
+                TransitionScene::new(
+                    TransitionEffect::Iris,
+                    TRANSITION_TIME,
+                    Box::<LevelScene>::default(),
+                )
+                .covering(level_data.root),
+                SceneStage::Start,
+            )
+        }
         LevelState::Quit => {
             commands.entity(level_data.root).despawn_recursive();
             commands.remove_resource::<Level>();
             commands.remove_resource::<LevelData>();
+            commands.remove_resource::<RespawnPoint>();
             lifes.count = 0;
             SceneResult::Pop(SceneStage::Resume)
         }
         LevelState::Dead => {
             lifes.count -= 1;
-            commands.entity(level_data.root).despawn_recursive();
             commands.remove_resource::<LevelData>();
-            SceneResult::Replace(Box::<LostLifeScene>::default(), SceneStage::Start)
+            SceneResult::Replace(
+                TransitionScene::new(
+                    TransitionEffect::Fade,
+                    TRANSITION_TIME,
+                    Box::<LostLifeScene>::default(),
+                )
+                .covering(level_data.root),
+                SceneStage::Start,
+            )
         }
         LevelState::Next => {
-            commands.entity(level_data.root).despawn_recursive();
             commands.remove_resource::<LevelData>();
-            if level.0 == 4 {
+            commands.remove_resource::<RespawnPoint>();
+            if progress.unlock(level.0 + 1)
+                && let Err(error) = progress.save(PROGRESS_PATH)
+            {
+                log::error!("Unable to save progress: {error}");
+            }
+            if level.0 == LEVEL_COUNT - 1 {
                 commands.remove_resource::<Level>();
                 lifes.count = 0;
-                SceneResult::Replace(Box::<GameCompleteScene>::default(), SceneStage::Start)
+                SceneResult::Replace(
+                    TransitionScene::new(
+                        TransitionEffect::Fade,
+                        TRANSITION_TIME,
+                        Box::<GameCompleteScene>::default(),
+                    )
+                    .covering(level_data.root),
+                    SceneStage::Start,
+                )
             } else {
                 level.0 += 1;
-                SceneResult::Replace(Box::<LevelOpeningScene>::default(), SceneStage::Start)
+                SceneResult::Replace(
+                    TransitionScene::new(
+                        TransitionEffect::Wipe,
+                        TRANSITION_TIME,
+                        Box::<LevelOpeningScene>::default(),
+                    )
+                    .covering(level_data.root),
+                    SceneStage::Start,
+                )
             }
         }
         _ => SceneResult::Ok(SceneStage::Update),
     }
 }
 
-fn camera_properties(window_size: Vec2) -> (f32, f32, f32) {
+fn camera_properties(window_size: Vec2, scaling: Scaling) -> (f32, f32, f32) {
     let viewport_height = window_size.y - TITLE_HEIGHT;
-    let zoom = viewport_height / SCREEN_HEIGHT;
-    let aspect = window_size.x / viewport_height;
-    let camera_max = (TOTAL_SCREEN_WIDTH - SCREEN_HEIGHT * aspect) / 2.0;
-    let camera_min = (-TOTAL_SCREEN_WIDTH + SCREEN_HEIGHT * aspect) / 2.0;
+    let zoom = scaling.zoom(viewport_height / SCREEN_HEIGHT);
+    let visible_width = window_size.x / zoom;
+    let camera_max = (TOTAL_SCREEN_WIDTH - visible_width) / 2.0;
+    let camera_min = (-TOTAL_SCREEN_WIDTH + visible_width) / 2.0;
     (zoom, camera_min, camera_max)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{audio::RecordingBackend, input::actions, player::floor_map};
+
+    const FLOOR: usize = 100;
+
+    fn player(y: f32, state: PlayerState) -> Player {
+        Player {
+            state,
+            ..Player::new(Vec2::new(1000.0, y))
+        }
+    }
+
+    /// Sounds played by one update from `previous` to `current`.
+    fn sounds(
+        previous: (LevelState, PlayerState),
+        current: (LevelState, PlayerState),
+    ) -> Vec<Sound> {
+        let (mut audio, recorder) = RecordingBackend::audio();
+        if let Some(sound) = update_sound(previous, current) {
+            audio.play(sound);
+        }
+        recorder.sounds()
+    }
+
+    #[test]
+    fn jump_plays_jump() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = player((FLOOR - 16) as f32, PlayerState::Standing);
+        player.update_classic(&actions(&[Action::Jump]), 1.0 / 60.0, &hit_map);
+        assert_eq!(player.state, PlayerState::Jumping);
+        assert_eq!(
+            sounds(
+                (LevelState::Play, PlayerState::Standing),
+                (LevelState::Play, player.state)
+            ),
+            [Sound::Jump]
+        );
+    }
+
+    #[test]
+    fn landing_plays_land() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = player((FLOOR - 16) as f32 - 0.5, PlayerState::Falling);
+        player.update_classic(&actions(&[]), 1.0 / 60.0, &hit_map);
+        assert_eq!(player.state, PlayerState::Standing);
+        assert_eq!(
+            sounds(
+                (LevelState::Play, PlayerState::Falling),
+                (LevelState::Play, player.state)
+            ),
+            [Sound::Land]
+        );
+    }
+
+    #[test]
+    fn death_plays_death() {
+        assert_eq!(
+            sounds(
+                (LevelState::Play, PlayerState::Standing),
+                (LevelState::Dying, PlayerState::Standing)
+            ),
+            [Sound::Death]
+        );
+        // A lethal fall squashes the player instead of landing
+        assert_eq!(
+            sounds(
+                (LevelState::Play, PlayerState::Falling),
+                (LevelState::Play, PlayerState::Squashed)
+            ),
+            [Sound::Death]
+        );
+    }
+
+    #[test]
+    fn exit_plays_exit() {
+        assert_eq!(
+            sounds(
+                (LevelState::Play, PlayerState::Standing),
+                (LevelState::Exiting, PlayerState::Standing)
+            ),
+            [Sound::Exit]
+        );
+    }
+
+    #[test]
+    fn walking_is_silent() {
+        assert!(
+            sounds(
+                (LevelState::Play, PlayerState::Standing),
+                (LevelState::Play, PlayerState::Standing)
+            )
+            .is_empty()
+        );
+        assert!(
+            sounds(
+                (LevelState::Dying, PlayerState::Standing),
+                (LevelState::Dying, PlayerState::Dying)
+            )
+            .is_empty()
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use anyhow::bail;
+use quad::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::Vec2,
+    ty::BoxedFuture,
+};
+
+use crate::{
+    config::{Choice, Physics},
+    key_value,
+};
+
+/// Image scrolled behind or in front of the level.
+pub struct LayerInfo {
+    /// Image path relative to the level directory.
+    pub image: String,
+    /// Horizontal speed relative to the level, 0 stays still and 1 moves with the level.
+    pub scroll: f32,
+    /// Tiles the image horizontally over the whole scrolled range.
+    pub repeat: bool,
+    pub offset_y: f32,
+    /// Depth, the level is drawn at 1 and the player at 2.
+    pub z: f32,
+}
+
+impl LayerInfo {
+    fn parse(value: &str) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+        let [image, scroll, repeat, offset_y, z] = parts[..] else {
+            bail!("Invalid layer: {}", value);
+        };
+        let repeat = match repeat {
+            "repeat" => true,
+            "once" => false,
+            _ => bail!("Invalid layer repeat: {}", repeat),
+        };
+        Ok(Self {
+            image: image.to_string(),
+            scroll: scroll.parse()?,
+            repeat,
+            offset_y: offset_y.parse()?,
+            z: z.parse()?,
+        })
+    }
+}
+
+pub struct LevelInfo {
+    /// Where the player respawns, sorted in the order they are passed, from right to left.
+    pub checkpoints: Vec<Vec2>,
+    pub layers: Vec<LayerInfo>,
+    /// Overrides the physics model chosen in the settings.
+    pub physics: Option<Physics>,
+    /// Falls higher than this many pixels kill the player.
+    pub lethal_fall: Option<f32>,
+    /// Background music, a path relative to the assets.
+    pub music: Option<String>,
+    pub camera_dead_zone: Option<f32>,
+    pub camera_look_ahead: Option<f32>,
+    pub camera_smooth_time: Option<f32>,
+    /// Position the camera shows for the given time when the level starts.
+    pub intro_pan: Option<(f32, f32)>,
+}
+
+impl LevelInfo {
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let text = std::str::from_utf8(bytes)?;
+        let mut checkpoints = Vec::new();
+        let mut layers = Vec::new();
+        let mut physics = None;
+        let mut lethal_fall = None;
+        let mut music = None;
+        let mut camera_dead_zone = None;
+        let mut camera_look_ahead = None;
+        let mut camera_smooth_time = None;
+        let mut intro_pan = None;
+        for (key, value) in key_value::parse(text)? {
+            match key {
+                "checkpoint" => match key_value::parse_list::<f32>(value)?[..] {
+                    [x, y] => checkpoints.push(Vec2::new(x, y)),
+                    _ => bail!("Invalid checkpoint: {}", value),
+                },
+                "layer" => layers.push(LayerInfo::parse(value)?),
+                "camera_dead_zone" => camera_dead_zone = Some(value.parse::<f32>()?),
+                "camera_look_ahead" => camera_look_ahead = Some(value.parse::<f32>()?),
+                "camera_smooth_time" => camera_smooth_time = Some(value.parse::<f32>()?),
+                "intro_pan" => match key_value::parse_list::<f32>(value)?[..] {
+                    [x, hold] => intro_pan = Some((x, hold)),
+                    _ => bail!("Invalid intro pan: {}", value),
+                },
+                "music" => music = Some(value.to_string()),
+                "lethal_fall" => lethal_fall = Some(value.parse::<f32>()?),
+                "physics" => match Physics::parse(value) {
+                    Some(value) => physics = Some(value),
+                    None => bail!("Invalid physics: {}", value),
+                },
+                _ => bail!("Unknown level property: {}", key),
+            }
+        }
+        checkpoints.sort_by(|a, b| b.x.total_cmp(&a.x));
+        Ok(Self {
+            checkpoints,
+            layers,
+            physics,
+            lethal_fall,
+            music,
+            camera_dead_zone,
+            camera_look_ahead,
+            camera_smooth_time,
+            intro_pan,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct LevelInfoLoader;
+
+impl AssetLoader for LevelInfoLoader {
+    fn load(
+        &self,
+        bytes: &[u8],
+        load_context: &mut LoadContext,
+    ) -> BoxedFuture<'_, anyhow::Result<()>> {
+        let result = LevelInfo::from_bytes(bytes)
+            .map(|level_info| load_context.set_default_asset(LoadedAsset::new(level_info)));
+        Box::pin(async move { result })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lvl"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{
+        constant::LEVEL_COUNT,
+        hit_map::{HitMap, Probe},
+        level::LevelAssets,
+        mouse::asset_path,
+    };
+
+    fn level_info(level: usize) -> LevelInfo {
+        let path = asset_path(LevelAssets::info_path(level as u32));
+        LevelInfo::from_bytes(&fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn levels_have_music() {
+        for level in 0..LEVEL_COUNT {
+            let music = level_info(level).music.expect("Missing music");
+            assert!(asset_path(&music).is_file(), "{music}");
+        }
+    }
+
+    #[test]
+    fn checkpoints_stand_on_the_floor() {
+        for level in 0..LEVEL_COUNT {
+            let path = asset_path(LevelAssets::hit_map_path(level as u32));
+            let hit_map = HitMap::from_rle_bytes(&fs::read(path).unwrap());
+            for position in level_info(level).checkpoints {
+                let (x, y) = (position.x, position.y);
+                for probe in [Probe::Left, Probe::Right, Probe::Top, Probe::Bottom] {
+                    assert!(
+                        !hit_map.check(probe, x, y),
+                        "{level}: {position} in a block"
+                    );
+                }
+                assert!(
+                    hit_map.check_bottom(x, y + 1.0),
+                    "{level}: {position} in the air"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn checkpoints_are_sorted_right_to_left() {
+        let info = LevelInfo::from_bytes(
+            b"checkpoint = 900, 40\ncheckpoint = 2400, 50\ncheckpoint = 1500, 60",
+        )
+        .unwrap();
+        assert_eq!(
+            info.checkpoints,
+            [
+                Vec2::new(2400.0, 50.0),
+                Vec2::new(1500.0, 60.0),
+                Vec2::new(900.0, 40.0)
+            ]
+        );
+    }
+}
@@ -1,8 +1,15 @@
 use quad::prelude::*;
 
 use crate::{
+    constant::TRANSITION_TIME,
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
     level::{Level, LevelScene},
-    mouse::{GameAssets, render_lifes},
+    locale::Localization,
+    mouse::render_lifes,
+    transition::{TransitionEffect, TransitionScene},
+    ui,
+    window::update_window,
 };
 
 pub struct LevelOpeningSchedule {
@@ -10,14 +17,6 @@ pub struct LevelOpeningSchedule {
     update: Schedule<(), SceneResult>,
 }
 
-const LEVEL_NAMES: [&str; 5] = [
-    "Horska udoli",
-    "Zaplavene jeskyne",
-    "Mesto v noci",
-    "Tajuplny zamek",
-    "Amazonska dzungle",
-];
-
 #[derive(Resource)]
 struct LevelOpeningData {
     root: Entity,
@@ -35,7 +34,11 @@ impl Scene for LevelOpeningScene {
                 .add(render_lifes)
                 .add(level_opening_start)
                 .build(),
-            update: Scheduler::single(level_opening_update),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(level_opening_update)
+                .build(),
         });
 
         match stage {
@@ -48,102 +51,23 @@ impl Scene for LevelOpeningScene {
 
 fn level_opening_start(
     mut commands: Commands,
-    assets: Res<GameAssets>,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
     level: Res<Level>,
 ) -> SceneResult {
-    let root = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                position_type: PositionType::Absolute,
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                ..Default::default()
-            },
-            color: Color::NONE.into(),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        style: Style {
-                            margin: UiRect {
-                                top: Val::Px(15.0),
-                                ..Default::default()
-                            },
-                            ..Default::default()
-                        },
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: LEVEL_NAMES[level.0].to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::YELLOW,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: format!("Level {}", level.0 + 1),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::RED,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                        position_type: PositionType::Absolute,
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: "STISKNI ENTER".to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 25.0,
-                                    color: Color::ORANGE_RED,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-        })
-        .id();
+    let number = (level.0 + 1).to_string();
+    let root = ui::message_screen(
+        &mut commands,
+        &fonts,
+        &[
+            (
+                &strings.format("level.title", &[("level", &number)]),
+                Color::RED,
+            ),
+            (strings.text(&format!("level.name.{number}")), Color::YELLOW),
+        ],
+        strings.text("prompt.enter"),
+    );
 
     commands.insert_resource(LevelOpeningData { root });
 
@@ -152,13 +76,20 @@ fn level_opening_start(
 
 fn level_opening_update(
     mut commands: Commands,
-    keyboard: Res<KeyboardInput>,
+    actions: Res<ActionState>,
     data: Res<LevelOpeningData>,
 ) -> SceneResult {
-    if keyboard.just_pressed(KeyCode::Enter) {
-        commands.entity(data.root).despawn_recursive();
+    if actions.just_pressed(Action::Confirm) {
         commands.remove_resource::<LevelOpeningData>();
-        SceneResult::Replace(Box::<LevelScene>::default(), SceneStage::Start)
+        SceneResult::Replace(
+            TransitionScene::new(
+                TransitionEffect::Iris,
+                TRANSITION_TIME,
+                Box::<LevelScene>::default(),
+            )
+            .covering(data.root),
+            SceneStage::Start,
+        )
     } else {
         SceneResult::Ok(SceneStage::Update)
     }
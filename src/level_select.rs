@@ -0,0 +1,185 @@
+use quad::prelude::*;
+
+use crate::{
+    audio::play_menu_sounds,
+    constant::*,
+    difficulty::Difficulty,
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
+    level::Level,
+    level_opening::LevelOpeningScene,
+    locale::Localization,
+    mouse::{GameAssets, Lifes},
+    progress::Progress,
+    transition::{TransitionEffect, TransitionScene},
+    ui::{self, MenuItem, MenuList},
+    window::update_window,
+};
+
+pub struct LevelSelectSchedule {
+    start: Schedule<(), SceneResult>,
+    update: Schedule<(), SceneResult>,
+}
+
+#[derive(Component)]
+struct Thumbnail;
+
+#[derive(Resource)]
+struct LevelSelectData {
+    root: Entity,
+    menu: MenuList,
+}
+
+#[derive(Default)]
+pub struct LevelSelectScene {
+    schedule: Option<LevelSelectSchedule>,
+}
+
+impl Scene for LevelSelectScene {
+    fn update(&mut self, stage: SceneStage, world: &mut World) -> SceneResult {
+        let schedule = self.schedule.get_or_insert_with(|| LevelSelectSchedule {
+            start: Scheduler::single(level_select_start),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(play_menu_sounds)
+                .add(level_select_update)
+                .build(),
+        });
+
+        match stage {
+            SceneStage::Start => schedule.start.run(world),
+            SceneStage::Update => schedule.update.run(world),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Crops the first screen of the level, levels are played from right to left so it is the rightmost one.
+pub fn level_thumbnail(foreground: &Image) -> Image {
+    let width = SCREEN_WIDTH as usize;
+    let height = SCREEN_HEIGHT as usize;
+    let row_size = TOTAL_SCREEN_WIDTH as usize * 4;
+    let offset = row_size - width * 4;
+    let data = foreground
+        .data
+        .chunks_exact(row_size)
+        .take(height)
+        .flat_map(|row| &row[offset..])
+        .copied()
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width: width as u32,
+            height: height as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn level_select_start(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
+    progress: Res<Progress>,
+    game_assets: Res<GameAssets>,
+) -> SceneResult {
+    let menu = MenuList::with_enabled(
+        (0..LEVEL_COUNT)
+            .map(|level| progress.is_unlocked(level))
+            .collect(),
+    );
+    let thumbnail = game_assets.level[menu.selected()].thumbnail.clone();
+    let root = ui::screen(&mut commands, Color::BLACK, |parent| {
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    ..Default::default()
+                },
+                color: Color::NONE.into(),
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                ui::column(parent, AlignItems::FlexStart, |parent| {
+                    let labels: Vec<String> = (1..=LEVEL_COUNT)
+                        .map(|number| strings.text(&format!("level.name.{number}")).to_string())
+                        .collect();
+                    let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+                    menu.spawn(parent, &fonts, &labels);
+                    ui::title(parent, &fonts, strings.text("level_select.title"));
+                });
+
+                parent
+                    .spawn_bundle(ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(SCREEN_WIDTH), Val::Px(SCREEN_HEIGHT)),
+                            margin: UiRect {
+                                left: Val::Px(30.0),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        image: thumbnail.into(),
+                        ..Default::default()
+                    })
+                    .insert(Thumbnail);
+            });
+        ui::footer(
+            parent,
+            &fonts,
+            strings.text("level_select.hint"),
+            Color::ORANGE_RED,
+        );
+    });
+
+    commands.insert_resource(LevelSelectData { root, menu });
+
+    SceneResult::Ok(SceneStage::Update)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn level_select_update(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    game_assets: Res<GameAssets>,
+    mut data: ResMut<LevelSelectData>,
+    mut lifes: ResMut<Lifes>,
+    difficulty: Res<Difficulty>,
+    mut items: Query<(&MenuItem, &mut Text)>,
+    mut thumbnail: Query<&mut UiImage, With<Thumbnail>>,
+) -> SceneResult {
+    if data.menu.navigate(&actions) {
+        data.menu.highlight(&mut items);
+        *thumbnail.single_mut() = game_assets.level[data.menu.selected()]
+            .thumbnail
+            .clone()
+            .into();
+    }
+
+    if actions.just_pressed(Action::Back) {
+        commands.entity(data.root).despawn_recursive();
+        commands.remove_resource::<LevelSelectData>();
+        SceneResult::Pop(SceneStage::Resume)
+    } else if actions.just_pressed(Action::Confirm) {
+        commands.remove_resource::<LevelSelectData>();
+        commands.insert_resource(Level(data.menu.selected()));
+        lifes.count = difficulty.lives();
+        SceneResult::Replace(
+            TransitionScene::new(
+                TransitionEffect::Fade,
+                TRANSITION_TIME,
+                Box::<LevelOpeningScene>::default(),
+            )
+            .covering(data.root),
+            SceneStage::Start,
+        )
+    } else {
+        SceneResult::Ok(SceneStage::Update)
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use quad::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    ty::BoxedFuture,
+};
+
+use crate::{
+    config::{Choice, Language},
+    key_value,
+};
+
+#[derive(Clone, Default)]
+pub struct StringTable {
+    strings: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn path(language: Language) -> String {
+        format!("locale/{}.lang", language.name())
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let text = std::str::from_utf8(bytes)?;
+        let strings = key_value::parse(text)?
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        Ok(Self { strings })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings.get(key).map(String::as_str)
+    }
+
+    #[cfg(test)]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.strings
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+/// String tables of all languages, so the language can be switched without reloading anything.
+/// Missing strings fall back to Czech and then to the key itself.
+#[derive(Resource)]
+pub struct Localization {
+    pub language: Language,
+    tables: Vec<StringTable>,
+}
+
+impl Localization {
+    pub fn new(language: Language, tables: Vec<StringTable>) -> Self {
+        Self { language, tables }
+    }
+
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        let table = |language: Language| {
+            let index = Language::ALL.iter().position(|&l| l == language)?;
+            self.tables.get(index)?.get(key)
+        };
+        table(self.language)
+            .or_else(|| table(Language::Czech))
+            .unwrap_or(key)
+    }
+
+    /// Replaces `{name}` placeholders in the string with the given values.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        args.iter()
+            .fold(self.text(key).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            })
+    }
+}
+
+#[derive(Default)]
+pub struct StringTableLoader;
+
+impl AssetLoader for StringTableLoader {
+    fn load(
+        &self,
+        bytes: &[u8],
+        load_context: &mut LoadContext,
+    ) -> BoxedFuture<'_, anyhow::Result<()>> {
+        let result = StringTable::from_bytes(bytes)
+            .map(|table| load_context.set_default_asset(LoadedAsset::new(table)));
+        Box::pin(async move { result })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["lang"]
+    }
+}
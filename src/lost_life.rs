@@ -1,8 +1,15 @@
 use quad::prelude::*;
 
 use crate::{
-    level::{Level, LevelScene},
-    mouse::{GameAssets, Lifes, render_lifes},
+    constant::TRANSITION_TIME,
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
+    level::{Level, LevelScene, RespawnPoint},
+    locale::Localization,
+    mouse::{Lifes, render_lifes},
+    transition::{TransitionEffect, TransitionScene},
+    ui,
+    window::update_window,
 };
 
 pub struct LostLifeSchedule {
@@ -27,7 +34,11 @@ impl Scene for LostLifeScene {
                 .add(render_lifes)
                 .add(lost_life_start)
                 .build(),
-            update: Scheduler::single(lost_life_update),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(lost_life_update)
+                .build(),
         });
 
         match stage {
@@ -40,86 +51,22 @@ impl Scene for LostLifeScene {
 
 fn lost_life_start(
     mut commands: Commands,
-    assets: Res<GameAssets>,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
     lifes: Res<Lifes>,
 ) -> SceneResult {
     let message = if lifes.count == 0 {
-        "Zemrel jsi"
+        strings.text("lost_life.dead")
     } else {
-        "Ztratil jsi zivot"
+        strings.text("lost_life.life")
     };
 
-    let root = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                position_type: PositionType::Absolute,
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                ..Default::default()
-            },
-            color: Color::NONE.into(),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: message.to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 30.0,
-                                    color: Color::GREEN,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                        position_type: PositionType::Absolute,
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::Center,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: "STISKNI ENTER".to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 25.0,
-                                    color: Color::ORANGE_RED,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-        })
-        .id();
+    let root = ui::message_screen(
+        &mut commands,
+        &fonts,
+        &[(message, Color::GREEN)],
+        strings.text("prompt.enter"),
+    );
 
     commands.insert_resource(LostLifeData { root });
 
@@ -128,18 +75,27 @@ fn lost_life_start(
 
 fn lost_life_update(
     mut commands: Commands,
-    keyboard: Res<KeyboardInput>,
+    actions: Res<ActionState>,
     data: Res<LostLifeData>,
     lifes: Res<Lifes>,
 ) -> SceneResult {
-    if keyboard.just_pressed(KeyCode::Enter) {
-        commands.entity(data.root).despawn_recursive();
+    if actions.just_pressed(Action::Confirm) {
         commands.remove_resource::<LostLifeData>();
         if lifes.count == 0 {
+            commands.entity(data.root).despawn_recursive();
             commands.remove_resource::<Level>();
+            commands.remove_resource::<RespawnPoint>();
             SceneResult::Pop(SceneStage::Resume)
         } else {
-            SceneResult::Replace(Box::<LevelScene>::default(), SceneStage::Start)
+            SceneResult::Replace(
+                TransitionScene::new(
+                    TransitionEffect::Iris,
+                    TRANSITION_TIME,
+                    Box::<LevelScene>::default(),
+                )
+                .covering(data.root),
+                SceneStage::Start,
+            )
         }
     } else {
         SceneResult::Ok(SceneStage::Update)
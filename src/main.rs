@@ -1,30 +1,79 @@
+mod animation;
+mod audio;
+mod camera;
+mod cheats;
+mod config;
+#[cfg(feature = "console")]
+mod console;
 mod constant;
+#[cfg(feature = "console")]
+mod debug_overlay;
+mod difficulty;
+mod filter;
+mod fonts;
 mod game_complete;
 mod hit_map;
+mod input;
+mod key_value;
 mod level;
+mod level_info;
 mod level_opening;
+mod level_select;
+mod locale;
 mod lost_life;
 mod menu;
 mod mouse;
+mod pause;
 mod player;
+mod progress;
+mod settings;
+mod transition;
+mod ui;
+mod window;
 
+use animation::{PlayerAnimation, PlayerAnimationLoader};
+use audio::Audio;
+use config::{CONFIG_PATH, Config};
+use filter::{Palette, PaletteLoader};
 use hit_map::{HitMap, HitMapLoader};
+use level_info::{LevelInfo, LevelInfoLoader};
+use locale::{StringTable, StringTableLoader};
 use mouse::MouseScene;
+use progress::{PROGRESS_PATH, Progress};
 use quad::prelude::*;
+use window::WindowState;
 
 fn main() {
+    let config = Config::load(CONFIG_PATH);
+    let (width, height) = config.window_size;
+    let audio = Audio::from_device(&config);
+    let progress = Progress::load(PROGRESS_PATH);
+
     Quad::new(QuadConfig {
         main_window: WindowDescriptor {
             title: "The Mouse 1".to_string(),
             size: LogicalSize {
-                width: 960.0,
-                height: 600.0,
+                width: width as f32,
+                height: height as f32,
             }
             .into(),
         },
         ..Default::default()
     })
+    .insert_resource(WindowState::new(&config))
+    .insert_resource(config)
+    .insert_resource(audio)
+    .insert_resource(progress.difficulty)
+    .insert_resource(progress)
+    .add_asset::<PlayerAnimation>()
+    .init_asset_loader::<PlayerAnimationLoader>()
+    .add_asset::<Palette>()
+    .init_asset_loader::<PaletteLoader>()
     .add_asset::<HitMap>()
     .init_asset_loader::<HitMapLoader>()
+    .add_asset::<LevelInfo>()
+    .init_asset_loader::<LevelInfoLoader>()
+    .add_asset::<StringTable>()
+    .init_asset_loader::<StringTableLoader>()
     .run(Box::<MouseScene>::default());
 }
@@ -1,9 +1,21 @@
 use quad::prelude::*;
 
 use crate::{
+    audio::{Audio, play_menu_sounds},
+    constant::TRANSITION_TIME,
+    difficulty::{Difficulty, DifficultyScene},
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
     level::Level,
     level_opening::LevelOpeningScene,
-    mouse::{GameAssets, Lifes, render_lifes},
+    level_select::LevelSelectScene,
+    locale::Localization,
+    mouse::{Lifes, render_lifes},
+    progress::Progress,
+    settings::SettingsScene,
+    transition::{TransitionEffect, TransitionScene},
+    ui::{self, MenuItem, MenuList},
+    window::update_window,
 };
 
 struct MenuSceneSchedule {
@@ -15,6 +27,7 @@ struct MenuSceneSchedule {
 #[derive(Resource)]
 struct MenuData {
     root: Entity,
+    menu: MenuList,
 }
 
 #[derive(Default)]
@@ -29,7 +42,12 @@ impl Scene for MenuScene {
                 .add(render_lifes)
                 .add(menu_init)
                 .build(),
-            update: Scheduler::single(menu_update),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(play_menu_sounds)
+                .add(menu_update)
+                .build(),
             pause: Scheduler::single(menu_pause),
         });
 
@@ -42,135 +60,128 @@ impl Scene for MenuScene {
     }
 }
 
-fn menu_init(mut commands: Commands, assets: Res<GameAssets>) -> SceneResult {
-    let root = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                position_type: PositionType::Absolute,
-                flex_direction: FlexDirection::Column,
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
-                ..Default::default()
-            },
-            color: Color::NONE.into(),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        flex_direction: FlexDirection::Column,
-                        align_items: AlignItems::FlexStart,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![
-                                TextSection {
-                                    value: "2.  ".to_string(),
-                                    style: TextStyle {
-                                        font: assets.font.clone(),
-                                        font_size: 30.0,
-                                        color: Color::GREEN,
-                                    },
-                                },
-                                TextSection {
-                                    value: " Konec".to_string(),
-                                    style: TextStyle {
-                                        font: assets.font.clone(),
-                                        font_size: 30.0,
-                                        color: Color::ORANGE_RED,
-                                    },
-                                },
-                            ],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![
-                                TextSection {
-                                    value: "1.  ".to_string(),
-                                    style: TextStyle {
-                                        font: assets.font.clone(),
-                                        font_size: 30.0,
-                                        color: Color::GREEN,
-                                    },
-                                },
-                                TextSection {
-                                    value: " Nova hra".to_string(),
-                                    style: TextStyle {
-                                        font: assets.font.clone(),
-                                        font_size: 30.0,
-                                        color: Color::ORANGE_RED,
-                                    },
-                                },
-                            ],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-
-            parent
-                .spawn_bundle(NodeBundle {
-                    style: Style {
-                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
-                        position_type: PositionType::Absolute,
-                        ..Default::default()
-                    },
-                    color: Color::NONE.into(),
-                    ..Default::default()
-                })
-                .with_children(|parent| {
-                    parent.spawn().insert_bundle(UiTextBundle {
-                        text: Text {
-                            sections: vec![TextSection {
-                                value: "Napsal O. Danek v roce 2022 v jazyce Rust".to_string(),
-                                style: TextStyle {
-                                    font: assets.font.clone(),
-                                    font_size: 25.0,
-                                    color: Color::PINK,
-                                },
-                            }],
-                            ..Default::default()
-                        },
-                        ..Default::default()
-                    });
-                });
-        })
-        .id();
-
-    commands.insert_resource(MenuData { root });
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum MenuEntry {
+    NewGame,
+    Continue,
+    LevelSelect,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+const ENTRIES: [MenuEntry; 6] = [
+    MenuEntry::NewGame,
+    MenuEntry::Continue,
+    MenuEntry::LevelSelect,
+    MenuEntry::Settings,
+    MenuEntry::HighScores,
+    MenuEntry::Quit,
+];
+
+impl MenuEntry {
+    fn key(self) -> &'static str {
+        match self {
+            Self::NewGame => "menu.new_game",
+            Self::Continue => "menu.continue",
+            Self::LevelSelect => "menu.level_select",
+            Self::Settings => "menu.settings",
+            Self::HighScores => "menu.high_scores",
+            Self::Quit => "menu.quit",
+        }
+    }
+
+    fn is_enabled(self, progress: &Progress) -> bool {
+        match self {
+            Self::Continue | Self::LevelSelect => progress.last_level() > 0,
+            Self::HighScores => false,
+            _ => true,
+        }
+    }
+}
+
+fn menu_init(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
+    progress: Res<Progress>,
+    mut audio: ResMut<Audio>,
+) -> SceneResult {
+    audio.play_music(None);
+    let menu = MenuList::with_enabled(
+        ENTRIES
+            .iter()
+            .map(|entry| entry.is_enabled(&progress))
+            .collect(),
+    );
+    let root = ui::screen(&mut commands, Color::NONE, |parent| {
+        ui::column(parent, AlignItems::FlexStart, |parent| {
+            let labels = ENTRIES.map(|entry| strings.text(entry.key()));
+            menu.spawn(parent, &fonts, &labels);
+        });
+        ui::footer(parent, &fonts, strings.text("menu.author"), Color::PINK);
+    });
+
+    commands.insert_resource(MenuData { root, menu });
 
     SceneResult::Ok(SceneStage::Update)
 }
 
-fn menu_update(keyboard: Res<KeyboardInput>) -> SceneResult {
-    if keyboard.just_pressed(KeyCode::Escape) || keyboard.just_pressed(KeyCode::Digit2) {
-        SceneResult::Quit
-    } else if keyboard.just_pressed(KeyCode::Digit1) {
-        SceneResult::Ok(SceneStage::Pause)
-    } else {
-        SceneResult::Ok(SceneStage::Update)
+fn menu_update(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut data: ResMut<MenuData>,
+    mut items: Query<(&MenuItem, &mut Text)>,
+) -> SceneResult {
+    if data.menu.navigate(&actions) {
+        data.menu.highlight(&mut items);
+    }
+
+    if actions.just_pressed(Action::Back) {
+        return SceneResult::Quit;
+    } else if !actions.just_pressed(Action::Confirm) {
+        return SceneResult::Ok(SceneStage::Update);
+    }
+
+    match ENTRIES[data.menu.selected()] {
+        MenuEntry::NewGame => {
+            commands.entity(data.root).despawn_recursive();
+            commands.remove_resource::<MenuData>();
+            SceneResult::Push(Box::<DifficultyScene>::default(), SceneStage::Start)
+        }
+        MenuEntry::Continue => SceneResult::Ok(SceneStage::Pause),
+        MenuEntry::LevelSelect => {
+            commands.entity(data.root).despawn_recursive();
+            commands.remove_resource::<MenuData>();
+            SceneResult::Push(Box::<LevelSelectScene>::default(), SceneStage::Start)
+        }
+        MenuEntry::Settings => {
+            commands.entity(data.root).despawn_recursive();
+            commands.remove_resource::<MenuData>();
+            SceneResult::Push(Box::<SettingsScene>::default(), SceneStage::Start)
+        }
+        MenuEntry::Quit => SceneResult::Quit,
+        MenuEntry::HighScores => SceneResult::Ok(SceneStage::Update),
     }
 }
 
 fn menu_pause(
     mut commands: Commands,
     data: Res<MenuData>,
+    progress: Res<Progress>,
+    difficulty: Res<Difficulty>,
     mut lifes: ResMut<Lifes>,
 ) -> SceneResult {
-    commands.entity(data.root).despawn_recursive();
     commands.remove_resource::<MenuData>();
-    commands.insert_resource(Level(0));
-    lifes.count = 5;
-    SceneResult::Push(Box::<LevelOpeningScene>::default(), SceneStage::Start)
+    commands.insert_resource(Level(progress.last_level()));
+    lifes.count = difficulty.lives();
+    SceneResult::Push(
+        TransitionScene::new(
+            TransitionEffect::Fade,
+            TRANSITION_TIME,
+            Box::<LevelOpeningScene>::default(),
+        )
+        .covering(data.root),
+        SceneStage::Start,
+    )
 }
@@ -1,12 +1,45 @@
 use quad::prelude::*;
 
-use crate::{hit_map::HitMap, level::LevelAssets, menu::MenuScene};
+use crate::{
+    animation::{PLAYER_ANIMATION_PATH, PlayerAnimation},
+    cheats::Cheats,
+    config::{Choice, Config, Language},
+    constant::{LEVEL_COUNT, MAX_LIFE_COUNT, TRANSITION_TIME},
+    filter::{PALETTE_PATH, Palette, scanline_image, vignette_image},
+    fonts::{FONTS, FontChain},
+    hit_map::HitMap,
+    input::ActionState,
+    level::LevelAssets,
+    level_info::LevelInfo,
+    level_select::level_thumbnail,
+    locale::{Localization, StringTable},
+    menu::MenuScene,
+    transition::{TransitionEffect, TransitionScene},
+};
+
+/// Path of an asset in the source tree, for tests reading assets without the asset server.
+#[cfg(test)]
+pub fn asset_path(path: impl AsRef<std::path::Path>) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join(path)
+}
 
 #[derive(Resource)]
 pub struct GameAssets {
-    pub font: Handle<Font>,
+    pub fonts: Vec<Handle<Font>>,
     pub level: Vec<LevelAssets>,
+    pub palette: Handle<Palette>,
+    /// Overlays of the scanline and vignette filters.
+    pub scanlines: Handle<Image>,
+    pub vignette: Handle<Image>,
+    /// Built from the sheet described by the player animation once it is loaded.
     pub player: Handle<TextureAtlas>,
+    /// Copy of the player atlas limited to the original palette, made once the palette filter
+    /// is selected.
+    pub palette_player: Handle<TextureAtlas>,
+    pub player_animation: Handle<PlayerAnimation>,
+    pub strings: Vec<Handle<StringTable>>,
 }
 
 #[derive(Resource)]
@@ -47,34 +80,47 @@ impl Scene for MouseScene {
 fn mouse_start(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    config: Res<Config>,
+    mut windows: ResMut<Windows>,
+    mut images: ResMut<Assets<Image>>,
 ) -> SceneResult {
     commands.insert_resource(ClearColor(Color::BLACK));
+    config.apply_window(windows.primary_mut());
 
-    let font = asset_server.load("helvetica.ttf");
-    let level = (0..5)
+    let fonts: Vec<Handle<Font>> = FONTS.iter().map(|&path| asset_server.load(path)).collect();
+    let font = fonts[0].clone();
+    let level = (0..LEVEL_COUNT as u32)
         .map(|level| LevelAssets {
             foreground: asset_server.load(LevelAssets::foreground_path(level)),
-            background: asset_server.load(LevelAssets::background_path(level)),
+            layers: Vec::new(),
             hit_map: asset_server.load(LevelAssets::hit_map_path(level)),
+            info: asset_server.load(LevelAssets::info_path(level)),
+            thumbnail: Handle::default(),
         })
         .collect();
 
-    let player_image = asset_server.load("player.tga");
-    let player = texture_atlases.add(TextureAtlas::from_grid(
-        player_image,
-        Vec2::new(10.0, 16.0),
-        18,
-        1,
-    ));
+    let strings = Language::ALL
+        .iter()
+        .map(|&language| asset_server.load(StringTable::path(language)))
+        .collect();
 
     commands.insert_resource(GameAssets {
-        font: font.clone(),
+        fonts,
         level,
-        player,
+        palette: asset_server.load(PALETTE_PATH),
+        scanlines: images.add(scanline_image()),
+        vignette: images.add(vignette_image()),
+        player: Handle::default(),
+        palette_player: Handle::default(),
+        player_animation: asset_server.load(PLAYER_ANIMATION_PATH),
+        strings,
     });
 
     commands.insert_resource(Lifes { count: 0 });
+    commands.insert_resource(ActionState::default());
+    commands.insert_resource(Cheats::default());
+    #[cfg(feature = "console")]
+    commands.insert_resource(crate::console::Console::default());
 
     commands
         .spawn_bundle(NodeBundle {
@@ -139,7 +185,7 @@ fn mouse_start(
                             ..Default::default()
                         })
                         .with_children(|parent| {
-                            for index in 0..5 {
+                            for index in 0..MAX_LIFE_COUNT {
                                 parent
                                     .spawn_bundle(NodeBundle {
                                         style: Style {
@@ -165,26 +211,93 @@ fn mouse_start(
     SceneResult::Ok(SceneStage::Update)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn mouse_update(
-    game_assets: Res<GameAssets>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<Config>,
+    mut game_assets: ResMut<GameAssets>,
     mut images: ResMut<Assets<Image>>,
-    texture_atlases: Res<Assets<TextureAtlas>>,
+    mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    animations: Res<Assets<PlayerAnimation>>,
+    palettes: Res<Assets<Palette>>,
     hit_maps: Res<Assets<HitMap>>,
+    level_infos: Res<Assets<LevelInfo>>,
+    string_tables: Res<Assets<StringTable>>,
+    font_assets: Res<Assets<Font>>,
 ) -> SceneResult {
+    for (index, level) in game_assets.level.iter_mut().enumerate() {
+        if level.layers.is_empty()
+            && let Some(info) = level_infos.get(&level.info)
+        {
+            level.layers = info
+                .layers
+                .iter()
+                .map(|layer| asset_server.load(LevelAssets::layer_path(index as u32, &layer.image)))
+                .collect();
+        }
+    }
     let levels_loaded = game_assets.level.iter().all(|level| {
         images.contains(&level.foreground)
-            && images.contains(&level.background)
             && hit_maps.contains(&level.hit_map)
+            && level_infos
+                .get(&level.info)
+                .is_some_and(|info| info.layers.len() == level.layers.len())
+            && level.layers.iter().all(|layer| images.contains(layer))
     });
-    let player_image_handle = &texture_atlases.get(&game_assets.player).unwrap().texture;
-    let player_loaded = images.contains(player_image_handle);
+    if game_assets.player == Handle::default()
+        && let Some(animation) = animations.get(&game_assets.player_animation)
+    {
+        game_assets.player =
+            texture_atlases.add(animation.atlas(asset_server.load(animation.image.as_str())));
+    }
+    let player_loaded = texture_atlases
+        .get(&game_assets.player)
+        .is_some_and(|atlas| images.contains(&atlas.texture));
+    let strings_loaded = game_assets
+        .strings
+        .iter()
+        .all(|strings| string_tables.contains(strings));
+
+    let fonts_loaded = game_assets
+        .fonts
+        .iter()
+        .all(|font| font_assets.contains(font));
+
+    let palette_loaded = palettes.contains(&game_assets.palette);
 
-    if levels_loaded && player_loaded {
-        for level in game_assets.level.iter() {
-            let image = images.get_mut(&level.background).unwrap();
-            image.sampler_descriptor.address_mode_u = AddressMode::Repeat;
+    if palette_loaded && levels_loaded && player_loaded && strings_loaded && fonts_loaded {
+        for level in game_assets.level.iter_mut() {
+            let info = level_infos.get(&level.info).unwrap();
+            for (layer, image) in info.layers.iter().zip(&level.layers) {
+                if layer.repeat {
+                    let image = images.get_mut(image).unwrap();
+                    image.sampler_descriptor.address_mode_u = AddressMode::Repeat;
+                }
+            }
+            let thumbnail = level_thumbnail(images.get(&level.foreground).unwrap());
+            level.thumbnail = images.add(thumbnail);
         }
-        SceneResult::Replace(Box::<MenuScene>::default(), SceneStage::Start)
+        let tables = game_assets
+            .strings
+            .iter()
+            .map(|strings| string_tables.get(strings).unwrap().clone())
+            .collect();
+        commands.insert_resource(Localization::new(config.language, tables));
+        let fonts = game_assets
+            .fonts
+            .iter()
+            .map(|font| (font.clone(), font_assets.get(font).unwrap().font.clone()))
+            .collect();
+        commands.insert_resource(FontChain::new(fonts));
+        SceneResult::Replace(
+            TransitionScene::new(
+                TransitionEffect::Fade,
+                TRANSITION_TIME,
+                Box::<MenuScene>::default(),
+            ),
+            SceneStage::Start,
+        )
     } else {
         SceneResult::Ok(SceneStage::Update)
     }
@@ -0,0 +1,113 @@
+use quad::prelude::*;
+
+use crate::{
+    audio::play_menu_sounds,
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
+    locale::Localization,
+    settings::SettingsScene,
+    ui::{self, MenuItem, MenuList},
+    window::update_window,
+};
+
+pub struct PauseSchedule {
+    start: Schedule<(), SceneResult>,
+    update: Schedule<(), SceneResult>,
+}
+
+#[derive(Resource, Copy, Clone, PartialEq, Eq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    Quit,
+}
+
+#[derive(Resource)]
+struct PauseData {
+    root: Entity,
+    menu: MenuList,
+}
+
+#[derive(Default)]
+pub struct PauseScene {
+    schedule: Option<PauseSchedule>,
+}
+
+impl Scene for PauseScene {
+    fn update(&mut self, stage: SceneStage, world: &mut World) -> SceneResult {
+        let schedule = self.schedule.get_or_insert_with(|| PauseSchedule {
+            start: Scheduler::single(pause_start),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(play_menu_sounds)
+                .add(pause_update)
+                .build(),
+        });
+
+        match stage {
+            SceneStage::Start | SceneStage::Resume => schedule.start.run(world),
+            SceneStage::Update => schedule.update.run(world),
+            _ => unreachable!(),
+        }
+    }
+}
+
+const ITEMS: [&str; 4] = [
+    "pause.resume",
+    "pause.restart",
+    "pause.settings",
+    "pause.quit",
+];
+
+fn pause_start(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
+) -> SceneResult {
+    let menu = MenuList::new(ITEMS.len());
+    let root = ui::screen(&mut commands, Color::rgba(0.0, 0.0, 0.0, 0.7), |parent| {
+        ui::column(parent, AlignItems::FlexStart, |parent| {
+            let labels = ITEMS.map(|key| strings.text(key));
+            menu.spawn(parent, &fonts, &labels);
+            ui::title(parent, &fonts, strings.text("pause.title"));
+        });
+    });
+
+    commands.insert_resource(PauseData { root, menu });
+
+    SceneResult::Ok(SceneStage::Update)
+}
+
+fn pause_update(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut data: ResMut<PauseData>,
+    mut items: Query<(&MenuItem, &mut Text)>,
+) -> SceneResult {
+    if data.menu.navigate(&actions) {
+        data.menu.highlight(&mut items);
+    }
+
+    let action = if actions.just_pressed(Action::Pause) || actions.just_pressed(Action::Back) {
+        PauseAction::Resume
+    } else if actions.just_pressed(Action::Confirm) {
+        match data.menu.selected() {
+            0 => PauseAction::Resume,
+            1 => PauseAction::Restart,
+            2 => {
+                commands.entity(data.root).despawn_recursive();
+                commands.remove_resource::<PauseData>();
+                return SceneResult::Push(Box::<SettingsScene>::default(), SceneStage::Start);
+            }
+            _ => PauseAction::Quit,
+        }
+    } else {
+        return SceneResult::Ok(SceneStage::Update);
+    };
+
+    commands.entity(data.root).despawn_recursive();
+    commands.remove_resource::<PauseData>();
+    commands.insert_resource(action);
+    SceneResult::Pop(SceneStage::Resume)
+}
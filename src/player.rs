@@ -1,6 +1,10 @@
 use quad::prelude::*;
 
-use crate::{constant::*, hit_map::HitMap};
+use crate::{
+    constant::*,
+    hit_map::{HitMap, Probe},
+    input::{Action, ActionState},
+};
 
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum PlayerOrientation {
@@ -8,11 +12,25 @@ pub enum PlayerOrientation {
     Right,
 }
 
+impl PlayerOrientation {
+    /// -1 for left and 1 for right.
+    pub fn direction(self) -> f32 {
+        match self {
+            PlayerOrientation::Left => -1.0,
+            PlayerOrientation::Right => 1.0,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum PlayerState {
     Standing,
     Jumping,
     Falling,
+    /// Landed from a lethal height, dies once the squash delay runs out.
+    Squashed,
+    /// Killed, shows the death clip until the level continues.
+    Dying,
 }
 
 #[derive(Component)]
@@ -20,84 +38,512 @@ pub struct Player {
     pub orientation: PlayerOrientation,
     pub state: PlayerState,
     pub position: Vec2,
+    pub velocity: Vec2,
+    /// Multiplier of the movement speed and acceleration, set by the difficulty.
+    pub speed: f32,
     pub jump_phase: f32,
+    /// Time left to jump after walking off a ledge.
+    pub coyote_time: f32,
+    /// Time left to perform a jump pressed just before landing.
+    pub jump_buffer: f32,
+    /// Highest point since the player left the ground.
+    pub fall_top: f32,
+    pub squash_time: f32,
+    /// Frames of the walk animation advanced by moving.
     pub animation_phase: f32,
+    /// Time spent in the current state.
+    pub animation_time: f32,
+    /// Positions the hit map was probed at during the last update, drawn by the debug overlay.
+    #[cfg(feature = "console")]
+    pub probes: Vec<(Probe, Vec2)>,
 }
 
 impl Player {
-    pub fn move_left(&mut self, time: &Time, hit_map: &HitMap) {
+    /// Standing at `position` facing left, at normal speed.
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            orientation: PlayerOrientation::Left,
+            state: PlayerState::Standing,
+            position,
+            velocity: Vec2::ZERO,
+            speed: 1.0,
+            jump_phase: 0.0,
+            coyote_time: 0.0,
+            jump_buffer: 0.0,
+            fall_top: position.y,
+            squash_time: 0.0,
+            animation_phase: 0.0,
+            animation_time: 0.0,
+            #[cfg(feature = "console")]
+            probes: Vec::new(),
+        }
+    }
+
+    pub fn update_classic(&mut self, actions: &ActionState, dt: f32, hit_map: &HitMap) {
+        self.update_jump_timers(actions, dt);
+        if self.state == PlayerState::Standing && self.can_fall(hit_map) {
+            self.state = PlayerState::Falling;
+        }
+
+        if self.jump_buffer > 0.0 {
+            self.jump(hit_map);
+        }
+        if actions.pressed(Action::Left) {
+            self.move_left(dt, hit_map)
+        } else if actions.pressed(Action::Right) {
+            self.move_right(dt, hit_map)
+        }
+
+        if self.state == PlayerState::Jumping {
+            self.move_up(actions.pressed(Action::Jump), dt, hit_map);
+        }
+        if self.state == PlayerState::Falling {
+            self.move_down(dt, hit_map);
+        }
+    }
+
+    /// Accelerates towards the maximum speed, gravity gives jumps a parabolic arc.
+    pub fn update_momentum(&mut self, actions: &ActionState, dt: f32, hit_map: &HitMap) {
+        self.update_jump_timers(actions, dt);
+        if self.state == PlayerState::Standing && self.can_fall(hit_map) {
+            self.state = PlayerState::Falling;
+        }
+
+        let direction = if actions.pressed(Action::Left) {
+            self.orientation = PlayerOrientation::Left;
+            -1.0
+        } else if actions.pressed(Action::Right) {
+            self.orientation = PlayerOrientation::Right;
+            1.0
+        } else {
+            0.0
+        };
+        if direction != 0.0 {
+            let max_speed = self.move_speed();
+            self.velocity.x = (self.velocity.x + direction * PLAYER_ACCELERATION * self.speed * dt)
+                .clamp(-max_speed, max_speed);
+        } else {
+            let speed = (self.velocity.x.abs() - PLAYER_FRICTION * dt).max(0.0);
+            self.velocity.x = speed.copysign(self.velocity.x);
+        }
+
+        if self.jump_buffer > 0.0 && self.jump(hit_map) {
+            self.velocity.y = -PLAYER_JUMP_VELOCITY;
+        }
+        if self.state == PlayerState::Jumping && !actions.pressed(Action::Jump) {
+            self.velocity.y = self.velocity.y.max(-PLAYER_JUMP_CUT_VELOCITY);
+        }
+
+        let x = self.position.x + self.velocity.x * dt;
+        let blocked = if self.velocity.x < 0.0 {
+            self.probe(hit_map, Probe::Left, x, self.position.y)
+        } else {
+            self.probe(hit_map, Probe::Right, x, self.position.y)
+        };
+        if blocked {
+            self.velocity.x = 0.0;
+        } else if self.velocity.x != 0.0 {
+            self.position.x = x;
+            self.animate(dt);
+        }
+
+        if self.state == PlayerState::Standing {
+            self.velocity.y = 0.0;
+            return;
+        }
+
+        self.velocity.y = (self.velocity.y + PLAYER_GRAVITY * dt).min(PLAYER_TERMINAL_VELOCITY);
+        let y = self.position.y + self.velocity.y * dt;
+        if self.velocity.y < 0.0 {
+            if self.probe(hit_map, Probe::Top, self.position.x, y) {
+                self.velocity.y = 0.0;
+                self.state = PlayerState::Falling;
+            } else {
+                self.position.y = y;
+            }
+        } else if self.probe(hit_map, Probe::Bottom, self.position.x, y) {
+            // Settle on the floor instead of stopping up to a few pixels above it
+            while self.position.y + 1.0 <= y && self.can_fall(hit_map) {
+                self.position.y += 1.0;
+            }
+            self.velocity.y = 0.0;
+            self.state = PlayerState::Standing;
+        } else {
+            self.position.y = y;
+            self.state = PlayerState::Falling;
+        }
+    }
+
+    pub fn move_left(&mut self, dt: f32, hit_map: &HitMap) {
         self.orientation = PlayerOrientation::Left;
-        let x = self.position.x - UPDATE_SPEED * time.delta_seconds();
-        if !hit_map.check_left(x, self.position.y) {
+        let x = self.position.x - self.move_speed() * dt;
+        if !self.probe(hit_map, Probe::Left, x, self.position.y) {
             self.position.x = x;
-            self.animate(time);
+            self.animate(dt);
         }
     }
 
-    pub fn move_right(&mut self, time: &Time, hit_map: &HitMap) {
+    pub fn move_right(&mut self, dt: f32, hit_map: &HitMap) {
         self.orientation = PlayerOrientation::Right;
-        let x = self.position.x + UPDATE_SPEED * time.delta_seconds();
-        if !hit_map.check_right(x, self.position.y) {
+        let x = self.position.x + self.move_speed() * dt;
+        if !self.probe(hit_map, Probe::Right, x, self.position.y) {
             self.position.x = x;
-            self.animate(time);
+            self.animate(dt);
         }
     }
 
-    pub fn move_up(&mut self, time: &Time, hit_map: &HitMap) {
-        let y = self.position.y - UPDATE_SPEED * time.delta_seconds();
-        if self.jump_phase >= PLAYER_JUMP_MAX || hit_map.check_top(self.position.x, y) {
+    /// Releasing the jump key ends the rise once the minimal height is reached.
+    pub fn move_up(&mut self, held: bool, dt: f32, hit_map: &HitMap) {
+        let y = self.position.y - self.move_speed() * dt;
+        let released = !held && self.jump_phase >= PLAYER_JUMP_MIN;
+        if released
+            || self.jump_phase >= PLAYER_JUMP_MAX
+            || self.probe(hit_map, Probe::Top, self.position.x, y)
+        {
             self.state = PlayerState::Falling;
         } else {
             self.position.y = y;
-            self.jump_phase += UPDATE_SPEED * time.delta_seconds();
+            self.jump_phase += self.move_speed() * dt;
         }
     }
 
-    pub fn move_down(&mut self, time: &Time, hit_map: &HitMap) {
-        let y = self.position.y + UPDATE_SPEED * time.delta_seconds();
-        if hit_map.check_bottom(self.position.x, y) {
+    pub fn move_down(&mut self, dt: f32, hit_map: &HitMap) {
+        let y = self.position.y + self.move_speed() * dt;
+        if self.probe(hit_map, Probe::Bottom, self.position.x, y) {
             self.state = PlayerState::Standing;
         } else {
             self.position.y = y;
         }
     }
 
-    pub fn jump(&mut self, hit_map: &HitMap) {
-        if self.state == PlayerState::Standing
-            && !hit_map.check_top(self.position.x, self.position.y - 1.0)
-        {
-            self.state = PlayerState::Jumping;
-            self.jump_phase = 0.0;
+    /// Walks out through the exit, walls are ignored.
+    pub fn walk_out(&mut self, dt: f32) {
+        self.position.x += self.orientation.direction() * UPDATE_SPEED * dt;
+        self.animate(dt);
+    }
+
+    /// Free movement through walls, used by the noclip cheat.
+    pub fn fly(&mut self, actions: &ActionState, dt: f32) {
+        let mut direction = Vec2::ZERO;
+        if actions.pressed(Action::Left) {
+            direction.x -= 1.0;
+        }
+        if actions.pressed(Action::Right) {
+            direction.x += 1.0;
+        }
+        if actions.pressed(Action::Up) {
+            direction.y -= 1.0;
+        }
+        if actions.pressed(Action::Down) {
+            direction.y += 1.0;
+        }
+        self.position += direction * UPDATE_SPEED * dt;
+        self.state = PlayerState::Falling;
+        self.fall_top = self.position.y;
+    }
+
+    /// Returns true if the jump has started.
+    pub fn jump(&mut self, hit_map: &HitMap) -> bool {
+        let on_ground = self.state == PlayerState::Standing
+            || (self.state == PlayerState::Falling && self.coyote_time > 0.0);
+        if !on_ground || self.probe(hit_map, Probe::Top, self.position.x, self.position.y - 1.0) {
+            return false;
         }
+        self.state = PlayerState::Jumping;
+        self.jump_phase = 0.0;
+        self.coyote_time = 0.0;
+        self.jump_buffer = 0.0;
+        true
     }
 
-    pub fn can_fall(&self, hit_map: &HitMap) -> bool {
-        !hit_map.check_bottom(self.position.x, self.position.y + 1.0)
+    fn update_jump_timers(&mut self, actions: &ActionState, dt: f32) {
+        self.jump_buffer = if actions.just_pressed(Action::Jump) {
+            PLAYER_JUMP_BUFFER
+        } else {
+            (self.jump_buffer - dt).max(0.0)
+        };
+        self.coyote_time = if self.state == PlayerState::Standing {
+            PLAYER_COYOTE_TIME
+        } else {
+            (self.coyote_time - dt).max(0.0)
+        };
     }
 
-    pub fn is_dead(&self, hit_map: &HitMap) -> bool {
-        hit_map.check_dead(self.position.x, self.position.y)
+    pub fn can_fall(&mut self, hit_map: &HitMap) -> bool {
+        !self.probe(
+            hit_map,
+            Probe::Bottom,
+            self.position.x,
+            self.position.y + 1.0,
+        )
     }
 
-    pub fn is_next_level(&self, hit_map: &HitMap) -> bool {
-        hit_map.check_next_level(self.position.x, self.position.y)
+    pub fn is_dead(&mut self, hit_map: &HitMap, tolerance: i32) -> bool {
+        #[cfg(feature = "console")]
+        for offset in -tolerance.abs()..=tolerance.abs() {
+            let origin = self.position + Vec2::new(offset as f32, 0.0);
+            self.probes.push((Probe::Dead, origin));
+        }
+        hit_map.check_dead(self.position.x, self.position.y, tolerance)
     }
 
-    pub fn sprite_index(&self) -> usize {
-        let index = match self.state {
-            PlayerState::Falling | PlayerState::Jumping => 0,
-            PlayerState::Standing => 1 + self.animation_phase as usize,
+    pub fn is_next_level(&mut self, hit_map: &HitMap) -> bool {
+        self.probe(hit_map, Probe::NextLevel, self.position.x, self.position.y)
+    }
+
+    fn probe(&mut self, hit_map: &HitMap, probe: Probe, x: f32, y: f32) -> bool {
+        #[cfg(feature = "console")]
+        self.probes.push((probe, Vec2::new(x, y)));
+        hit_map.check(probe, x, y)
+    }
+
+    /// Returns the height of the fall on the frame the player lands, zero otherwise.
+    pub fn track_fall(&mut self) -> f32 {
+        self.fall_top = self.fall_top.min(self.position.y);
+        if self.state != PlayerState::Standing {
+            return 0.0;
+        }
+        let height = self.position.y - self.fall_top;
+        self.fall_top = self.position.y;
+        height
+    }
+
+    pub fn squash(&mut self) {
+        self.state = PlayerState::Squashed;
+        self.squash_time = PLAYER_SQUASH_DELAY;
+    }
+
+    /// Returns true once the squash delay has run out.
+    pub fn update_squash(&mut self, dt: f32) -> bool {
+        self.squash_time -= dt;
+        self.squash_time <= 0.0
+    }
+
+    fn move_speed(&self) -> f32 {
+        UPDATE_SPEED * self.speed
+    }
+
+    /// The walk cycle follows the distance walked.
+    fn animate(&mut self, dt: f32) {
+        self.animation_phase += self.move_speed() * ANIMATION_SPEED * dt;
+    }
+}
+
+/// Empty level with a solid floor starting at the given row.
+#[cfg(test)]
+pub fn floor_map(floor: usize) -> HitMap {
+    HitMap::from_fn(|_, y| if y >= floor { 1 } else { 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOOR: usize = 100;
+    /// Moves the classic player by exactly 0.9375 pixels per frame.
+    const DT: f32 = 1.0 / 64.0;
+    const STANDING_Y: f32 = (FLOOR - 16) as f32;
+
+    /// Runs one frame with `pressed` held, `just_pressed` follows from the previous frames.
+    fn update_classic(
+        player: &mut Player,
+        actions: &mut ActionState,
+        pressed: &[Action],
+        hit_map: &HitMap,
+    ) {
+        actions.update(pressed.iter().copied().collect());
+        player.update_classic(actions, DT, hit_map);
+    }
+
+    /// Highest point reached by a jump with the jump action held for `held` frames.
+    fn jump_top(held: usize) -> f32 {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player::new(Vec2::new(1000.0, STANDING_Y));
+        let mut actions = ActionState::default();
+        let mut top = player.position.y;
+        for frame in 0..200 {
+            let pressed: &[Action] = if frame < held { &[Action::Jump] } else { &[] };
+            update_classic(&mut player, &mut actions, pressed, &hit_map);
+            top = top.min(player.position.y);
+        }
+        assert_eq!(player.state, PlayerState::Standing);
+        top
+    }
+
+    #[test]
+    fn jump_pressed_before_landing_fires() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player {
+            state: PlayerState::Falling,
+            ..Player::new(Vec2::new(1000.0, STANDING_Y - 5.0))
+        };
+        let mut actions = ActionState::default();
+        update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+        let mut time = DT;
+        while player.state == PlayerState::Falling {
+            update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+            time += DT;
+        }
+        assert!(time < PLAYER_JUMP_BUFFER);
+        assert_eq!(player.state, PlayerState::Standing);
+
+        update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+        assert_eq!(player.state, PlayerState::Jumping);
+    }
+
+    #[test]
+    fn jump_pressed_too_early_is_dropped() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player {
+            state: PlayerState::Falling,
+            ..Player::new(Vec2::new(1000.0, STANDING_Y - 20.0))
         };
-        match self.orientation {
-            PlayerOrientation::Left => 9 + index,
-            PlayerOrientation::Right => index,
+        let mut actions = ActionState::default();
+        update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+        while player.state == PlayerState::Falling {
+            update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
         }
+        update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+        assert_eq!(player.state, PlayerState::Standing);
     }
 
-    fn animate(&mut self, time: &Time) {
-        self.animation_phase += UPDATE_SPEED * ANIMATION_SPEED * time.delta_seconds();
-        if self.animation_phase >= ANIMATION_COUNT {
-            self.animation_phase = 0.0;
+    #[test]
+    fn jump_after_leaving_ledge_fires_within_coyote_time() {
+        const LEDGE: usize = 1000;
+        let hit_map = HitMap::from_fn(|x, y| if y >= FLOOR && x >= LEDGE { 1 } else { 0 });
+        for (frames, jumps) in [(2, true), (10, false)] {
+            let mut player = Player::new(Vec2::new(LEDGE as f32, STANDING_Y));
+            let mut actions = ActionState::default();
+            while player.state == PlayerState::Standing {
+                update_classic(&mut player, &mut actions, &[Action::Left], &hit_map);
+            }
+            for _ in 0..frames {
+                update_classic(&mut player, &mut actions, &[], &hit_map);
+            }
+            assert!((frames as f32 * DT < PLAYER_COYOTE_TIME) == jumps);
+            update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+            assert_eq!(
+                player.state == PlayerState::Jumping,
+                jumps,
+                "{frames} frames"
+            );
         }
     }
+
+    #[test]
+    fn released_jump_stops_at_minimum_height() {
+        let height = STANDING_Y - jump_top(1);
+        assert!(height >= PLAYER_JUMP_MIN, "{height}");
+        assert!(height < PLAYER_JUMP_MIN + UPDATE_SPEED * DT, "{height}");
+    }
+
+    #[test]
+    fn held_jump_stops_at_maximum_height() {
+        let height = STANDING_Y - jump_top(200);
+        assert!(height >= PLAYER_JUMP_MAX, "{height}");
+        assert!(height < PLAYER_JUMP_MAX + UPDATE_SPEED * DT, "{height}");
+    }
+
+    /// Runs momentum updates for `frames` frames with `pressed` held.
+    fn run_momentum(player: &mut Player, pressed: &[Action], frames: usize, hit_map: &HitMap) {
+        let mut actions = ActionState::default();
+        for _ in 0..frames {
+            actions.update(pressed.iter().copied().collect());
+            player.update_momentum(&actions, DT, hit_map);
+        }
+    }
+
+    #[test]
+    fn momentum_speed_is_clamped_to_max_speed() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player::new(Vec2::new(1000.0, STANDING_Y));
+        run_momentum(&mut player, &[Action::Right], 64, &hit_map);
+        assert_eq!(player.velocity.x, player.move_speed());
+
+        let mut player = Player {
+            speed: 0.5,
+            ..Player::new(Vec2::new(1000.0, STANDING_Y))
+        };
+        run_momentum(&mut player, &[Action::Left], 64, &hit_map);
+        assert_eq!(player.velocity.x, -player.move_speed());
+    }
+
+    #[test]
+    fn friction_stops_the_player() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player {
+            velocity: Vec2::new(UPDATE_SPEED, 0.0),
+            ..Player::new(Vec2::new(1000.0, STANDING_Y))
+        };
+        let stop_frames = (UPDATE_SPEED / PLAYER_FRICTION / DT).ceil() as usize;
+        run_momentum(&mut player, &[], stop_frames, &hit_map);
+        assert_eq!(player.velocity.x, 0.0);
+
+        let position = player.position;
+        run_momentum(&mut player, &[], 10, &hit_map);
+        assert_eq!(player.position, position);
+    }
+
+    #[test]
+    fn jump_cut_limits_rise_speed_on_release() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player::new(Vec2::new(1000.0, STANDING_Y));
+        run_momentum(&mut player, &[Action::Jump], 1, &hit_map);
+        assert_eq!(player.state, PlayerState::Jumping);
+        assert!(player.velocity.y < -PLAYER_JUMP_CUT_VELOCITY);
+
+        run_momentum(&mut player, &[], 1, &hit_map);
+        assert!(player.velocity.y >= -PLAYER_JUMP_CUT_VELOCITY);
+    }
+
+    #[test]
+    fn landing_settles_on_the_floor_row() {
+        let hit_map = floor_map(FLOOR);
+        for start in [10.0, 40.25, 77.5] {
+            let mut player = Player {
+                state: PlayerState::Falling,
+                ..Player::new(Vec2::new(1000.0, start))
+            };
+            run_momentum(&mut player, &[], 200, &hit_map);
+            assert_eq!(player.state, PlayerState::Standing);
+            assert_eq!(player.position.y.round(), STANDING_Y, "from {start}");
+            assert!(!player.can_fall(&hit_map));
+        }
+    }
+
+    #[test]
+    fn fall_is_measured_from_the_highest_point() {
+        let mut player = Player::new(Vec2::new(1000.0, 80.0));
+        player.state = PlayerState::Jumping;
+        player.position.y = 60.0;
+        assert_eq!(player.track_fall(), 0.0);
+
+        player.state = PlayerState::Falling;
+        player.position.y = 90.0;
+        assert_eq!(player.track_fall(), 0.0);
+
+        player.state = PlayerState::Standing;
+        player.position.y = 95.0;
+        assert_eq!(player.track_fall(), 35.0);
+        assert_eq!(player.track_fall(), 0.0);
+    }
+
+    #[test]
+    fn jump_onto_the_same_floor_falls_its_height() {
+        let hit_map = floor_map(FLOOR);
+        let mut player = Player::new(Vec2::new(1000.0, STANDING_Y));
+        let mut actions = ActionState::default();
+        let mut frames = 0;
+        let fall = loop {
+            update_classic(&mut player, &mut actions, &[Action::Jump], &hit_map);
+            let fall = player.track_fall();
+            if player.state == PlayerState::Standing && frames > 0 {
+                break fall;
+            }
+            frames += 1;
+            assert!(frames < 200, "never landed");
+        };
+        assert!(fall >= PLAYER_JUMP_MAX, "{fall}");
+        assert!(fall < PLAYER_JUMP_MAX + UPDATE_SPEED * DT, "{fall}");
+    }
 }
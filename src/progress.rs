@@ -0,0 +1,124 @@
+use std::{fs, path::Path};
+
+use quad::prelude::*;
+
+use crate::{config::Choice, constant::LEVEL_COUNT, difficulty::Difficulty, key_value};
+
+pub const PROGRESS_PATH: &str = "mouse1rs.sav";
+
+/// Levels the player has reached so far and the difficulty of the last new game,
+/// persisted between runs.
+#[derive(Resource)]
+pub struct Progress {
+    unlocked: usize,
+    pub difficulty: Difficulty,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self {
+            unlocked: 1,
+            difficulty: Difficulty::Normal,
+        }
+    }
+}
+
+impl Progress {
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let mut progress = Self::default();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => return progress,
+        };
+        let pairs = match key_value::parse(&text) {
+            Ok(pairs) => pairs,
+            Err(error) => {
+                log::warn!("Unable to parse progress: {error}");
+                return progress;
+            }
+        };
+
+        for (key, value) in pairs {
+            match (key, value.parse::<usize>(), Difficulty::parse(value)) {
+                ("unlocked", Ok(unlocked), _) => progress.unlocked = unlocked.clamp(1, LEVEL_COUNT),
+                ("difficulty", _, Some(difficulty)) => progress.difficulty = difficulty,
+                _ => log::warn!("Invalid progress entry: {key} = {value}"),
+            }
+        }
+        progress
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let text = format!(
+            "unlocked = {}\ndifficulty = {}\n",
+            self.unlocked,
+            self.difficulty.name()
+        );
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    pub fn is_unlocked(&self, level: usize) -> bool {
+        level < self.unlocked
+    }
+
+    /// Last unlocked level, where Continue starts.
+    pub fn last_level(&self) -> usize {
+        self.unlocked - 1
+    }
+
+    /// Returns true if the level has not been unlocked before.
+    pub fn unlock(&mut self, level: usize) -> bool {
+        if self.is_unlocked(level) || level >= LEVEL_COUNT {
+            return false;
+        }
+        self.unlocked = level + 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::key_value::temp_path;
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let mut progress = Progress {
+            difficulty: Difficulty::Hard,
+            ..Default::default()
+        };
+        progress.unlock(3);
+
+        let path = temp_path("round_trip.sav");
+        progress.save(&path).unwrap();
+        let loaded = Progress::load(&path);
+        fs::remove_file(path).unwrap();
+        assert_eq!(loaded.last_level(), 3);
+        assert_eq!(loaded.difficulty, Difficulty::Hard);
+    }
+
+    #[test]
+    fn invalid_file_gives_defaults() {
+        let path = temp_path("invalid.sav");
+        fs::write(&path, "unlocked = many\ndifficulty = impossible\n").unwrap();
+        let loaded = Progress::load(&path);
+        fs::remove_file(path).unwrap();
+        assert_eq!(loaded.last_level(), 0);
+        assert_eq!(loaded.difficulty, Difficulty::Normal);
+    }
+
+    #[test]
+    fn unlock_never_lowers_progress() {
+        let mut progress = Progress::default();
+        assert!(progress.unlock(2));
+        assert!(progress.is_unlocked(1));
+        assert!(!progress.unlock(1));
+        assert!(!progress.unlock(0));
+        assert_eq!(progress.last_level(), 2);
+        assert!(!progress.unlock(LEVEL_COUNT));
+        assert_eq!(progress.last_level(), 2);
+    }
+}
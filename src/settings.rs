@@ -0,0 +1,209 @@
+use quad::prelude::*;
+
+use crate::{
+    audio::{Audio, play_menu_sounds},
+    config::{CONFIG_PATH, Choice, Config, step_volume},
+    fonts::FontChain,
+    input::{Action, ActionState, update_actions},
+    locale::Localization,
+    ui::{self, MenuItem, MenuList},
+    window::update_window,
+};
+
+pub struct SettingsSchedule {
+    start: Schedule<(), SceneResult>,
+    update: Schedule<(), SceneResult>,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Setting {
+    DisplayMode,
+    WindowSize,
+    Scaling,
+    Filter,
+    Controls,
+    Physics,
+    SoundVolume,
+    MusicVolume,
+    Language,
+}
+
+const SETTINGS: [Setting; 9] = [
+    Setting::DisplayMode,
+    Setting::WindowSize,
+    Setting::Scaling,
+    Setting::Filter,
+    Setting::Controls,
+    Setting::Physics,
+    Setting::SoundVolume,
+    Setting::MusicVolume,
+    Setting::Language,
+];
+
+impl Setting {
+    fn key(self) -> &'static str {
+        match self {
+            Self::DisplayMode => "settings.display_mode",
+            Self::WindowSize => "settings.window_size",
+            Self::Scaling => "settings.scaling",
+            Self::Filter => "settings.filter",
+            Self::Controls => "settings.controls",
+            Self::Physics => "settings.physics",
+            Self::SoundVolume => "settings.sound_volume",
+            Self::MusicVolume => "settings.music_volume",
+            Self::Language => "settings.language",
+        }
+    }
+
+    fn value(self, config: &Config, strings: &Localization) -> String {
+        let choice = match self {
+            Self::DisplayMode => config.display_mode.name(),
+            Self::WindowSize => {
+                return format!("{}x{}", config.window_size.0, config.window_size.1);
+            }
+            Self::Scaling => config.scaling.name(),
+            Self::Filter => config.filter.name(),
+            Self::Controls => config.controls.name(),
+            Self::Physics => config.physics.name(),
+            Self::SoundVolume => return config.sound_volume.to_string(),
+            Self::MusicVolume => return config.music_volume.to_string(),
+            Self::Language => config.language.name(),
+        };
+        strings
+            .text(&format!("{}.{}", self.key(), choice))
+            .to_string()
+    }
+
+    fn change(self, config: &mut Config, forward: bool) {
+        match self {
+            Self::DisplayMode => config.display_mode = config.display_mode.cycle(forward),
+            Self::WindowSize => config.cycle_window_size(forward),
+            Self::Scaling => config.scaling = config.scaling.cycle(forward),
+            Self::Filter => config.filter = config.filter.cycle(forward),
+            Self::Controls => config.set_controls(config.controls.cycle(forward)),
+            Self::Physics => config.physics = config.physics.cycle(forward),
+            Self::SoundVolume => config.sound_volume = step_volume(config.sound_volume, forward),
+            Self::MusicVolume => config.music_volume = step_volume(config.music_volume, forward),
+            Self::Language => config.language = config.language.cycle(forward),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct SettingsData {
+    root: Entity,
+    menu: MenuList,
+}
+
+#[derive(Default)]
+pub struct SettingsScene {
+    schedule: Option<SettingsSchedule>,
+}
+
+impl Scene for SettingsScene {
+    fn update(&mut self, stage: SceneStage, world: &mut World) -> SceneResult {
+        let schedule = self.schedule.get_or_insert_with(|| SettingsSchedule {
+            start: Scheduler::single(settings_start),
+            update: Scheduler::chain(world)
+                .add(update_actions)
+                .add(update_window)
+                .add(play_menu_sounds)
+                .add(settings_update)
+                .build(),
+        });
+
+        match stage {
+            SceneStage::Start => schedule.start.run(world),
+            SceneStage::Update => schedule.update.run(world),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn settings_start(
+    mut commands: Commands,
+    fonts: Res<FontChain>,
+    strings: Res<Localization>,
+    config: Res<Config>,
+    data: Option<Res<SettingsData>>,
+) -> SceneResult {
+    // Rebuilt after a language change, the selection stays
+    let mut menu = MenuList::new(SETTINGS.len());
+    if let Some(data) = data {
+        menu.select(data.menu.selected());
+    }
+    let rows: Vec<_> = SETTINGS
+        .iter()
+        .map(|setting| {
+            (
+                strings.text(setting.key()),
+                setting.value(&config, &strings),
+            )
+        })
+        .collect();
+    let root = ui::screen(&mut commands, Color::BLACK, |parent| {
+        ui::column(parent, AlignItems::FlexStart, |parent| {
+            menu.spawn_rows(parent, &fonts, &rows);
+            ui::title(parent, &fonts, strings.text("settings.title"));
+        });
+        ui::footer(
+            parent,
+            &fonts,
+            strings.text("settings.hint"),
+            Color::ORANGE_RED,
+        );
+    });
+
+    commands.insert_resource(SettingsData { root, menu });
+
+    SceneResult::Ok(SceneStage::Update)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn settings_update(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    mut data: ResMut<SettingsData>,
+    mut config: ResMut<Config>,
+    mut strings: ResMut<Localization>,
+    fonts: Res<FontChain>,
+    mut windows: ResMut<Windows>,
+    mut audio: ResMut<Audio>,
+    mut items: Query<(&MenuItem, &mut Text)>,
+) -> SceneResult {
+    if actions.just_pressed(Action::Back) {
+        commands.entity(data.root).despawn_recursive();
+        commands.remove_resource::<SettingsData>();
+        return SceneResult::Pop(SceneStage::Resume);
+    }
+
+    if data.menu.navigate(&actions) {
+        data.menu.highlight(&mut items);
+    }
+
+    let setting = SETTINGS[data.menu.selected()];
+    let forward = actions.just_pressed(Action::Right);
+    if forward || actions.just_pressed(Action::Left) {
+        setting.change(&mut config, forward);
+        if matches!(setting, Setting::DisplayMode | Setting::WindowSize) {
+            config.apply_window(windows.primary_mut());
+        }
+        if matches!(setting, Setting::SoundVolume | Setting::MusicVolume) {
+            audio.set_volume(&config);
+        }
+        if let Err(error) = config.save(CONFIG_PATH) {
+            log::error!("Unable to save configuration: {error}");
+        }
+        if setting == Setting::Language {
+            // Rebuild the whole screen in the new language
+            strings.language = config.language;
+            commands.entity(data.root).despawn_recursive();
+            return SceneResult::Ok(SceneStage::Start);
+        }
+        data.menu.set_values(&mut items, &fonts, |index| {
+            SETTINGS[index].value(&config, &strings)
+        });
+    }
+
+    SceneResult::Ok(SceneStage::Update)
+}
@@ -0,0 +1,264 @@
+use quad::prelude::*;
+
+/// Width of the transition mask, it is stretched over the whole window.
+const MASK_WIDTH: u32 = 160;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum TransitionEffect {
+    /// Fades to black and back.
+    Fade,
+    /// Black curtain sliding over the screen from the right and off to the left.
+    Wipe,
+    /// Circle closing to the center of the screen and opening again.
+    Iris,
+}
+
+impl TransitionEffect {
+    /// Opacity of the cover at the normalized mask position, `progress` goes from 0 to 1
+    /// while the new scene is revealed.
+    fn cover(self, progress: f32, x: f32, y: f32, aspect: f32) -> f32 {
+        let covered = match self {
+            TransitionEffect::Fade => return 1.0 - progress,
+            TransitionEffect::Wipe => x < 1.0 - progress,
+            TransitionEffect::Iris => {
+                let dx = (x - 0.5) * aspect;
+                let dy = y - 0.5;
+                let radius = progress * (aspect * aspect + 1.0).sqrt() / 2.0;
+                dx * dx + dy * dy > radius * radius
+            }
+        };
+        if covered { 1.0 } else { 0.0 }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Phase {
+    /// The old scene is being covered.
+    Cover,
+    /// The old scene is gone and the new one can start.
+    Covered,
+    /// The new scene is being revealed.
+    Reveal,
+}
+
+#[derive(Resource)]
+struct Transition {
+    effect: TransitionEffect,
+    /// Length of each of the two phases.
+    duration: f32,
+    elapsed: f32,
+    phase: Phase,
+    /// Entities of the old scene, despawned once they are covered.
+    covered: Vec<Entity>,
+    root: Entity,
+    /// Shared by both phases and removed at the end.
+    mask: Handle<Image>,
+}
+
+impl Transition {
+    fn progress(&self) -> f32 {
+        self.elapsed / self.duration.max(f32::EPSILON)
+    }
+
+    fn cover_progress(&self) -> f32 {
+        match self.phase {
+            Phase::Cover => 1.0 - self.progress(),
+            Phase::Covered => 0.0,
+            Phase::Reveal => self.progress(),
+        }
+    }
+}
+
+/// Parameters handed over from the scene to the start system.
+#[derive(Resource)]
+struct TransitionData {
+    effect: TransitionEffect,
+    duration: f32,
+    covered: Vec<Entity>,
+}
+
+pub struct TransitionSchedule {
+    start: Schedule<(), SceneResult>,
+    update: Schedule<(), SceneResult>,
+    reveal: Schedule<(), SceneResult>,
+}
+
+/// Covers the old scene with a transition effect and reveals the wrapped scene once it has
+/// started. The wrapped scene doesn't update, and so ignores input, until the transition is over.
+pub struct TransitionScene {
+    scene: Box<dyn Scene>,
+    effect: TransitionEffect,
+    duration: f32,
+    covered: Vec<Entity>,
+    active: bool,
+    schedule: Option<TransitionSchedule>,
+}
+
+impl TransitionScene {
+    /// The transition takes `duration` seconds, half of it covering and half revealing.
+    pub fn new(effect: TransitionEffect, duration: f32, scene: Box<dyn Scene>) -> Box<Self> {
+        Box::new(Self {
+            scene,
+            effect,
+            duration: duration / 2.0,
+            covered: Vec::new(),
+            active: false,
+            schedule: None,
+        })
+    }
+
+    /// Root of the old scene, it stays on the screen until it is covered and is then despawned.
+    pub fn covering(mut self: Box<Self>, root: Entity) -> Box<Self> {
+        self.covered.push(root);
+        self
+    }
+}
+
+impl Scene for TransitionScene {
+    fn update(&mut self, stage: SceneStage, world: &mut World) -> SceneResult {
+        let schedule = self.schedule.get_or_insert_with(|| TransitionSchedule {
+            start: Scheduler::single(transition_start),
+            update: Scheduler::single(transition_update),
+            reveal: Scheduler::single(transition_reveal),
+        });
+
+        match stage {
+            SceneStage::Start => {
+                world.insert_resource(TransitionData {
+                    effect: self.effect,
+                    duration: self.duration,
+                    covered: std::mem::take(&mut self.covered),
+                });
+                self.active = true;
+                schedule.start.run(world)
+            }
+            SceneStage::Update if self.active => {
+                let mut result = schedule.update.run(world);
+                let covered = world
+                    .get_resource::<Transition>()
+                    .is_some_and(|transition| transition.phase == Phase::Covered);
+                if covered {
+                    // The mask is spawned again so it stays on top of the new scene
+                    result = self.scene.update(SceneStage::Start, world);
+                    schedule.reveal.run(world);
+                }
+                self.active = world.contains_resource::<Transition>();
+                result
+            }
+            _ => self.scene.update(stage, world),
+        }
+    }
+}
+
+fn spawn_mask(commands: &mut Commands, mask: &Handle<Image>) -> Entity {
+    commands
+        .spawn_bundle(ImageBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            image: mask.clone().into(),
+            ..Default::default()
+        })
+        .id()
+}
+
+fn transition_start(
+    mut commands: Commands,
+    mut data: ResMut<TransitionData>,
+    windows: Res<Windows>,
+    mut images: ResMut<Assets<Image>>,
+) -> SceneResult {
+    let window_size = windows.primary().size();
+    let height = (MASK_WIDTH as f32 * window_size.y / window_size.x.max(1.0)).ceil() as u32;
+    let height = height.max(1);
+    let mask = images.add(Image::new(
+        Extent3d {
+            width: MASK_WIDTH,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        mask_data(data.effect, 1.0, MASK_WIDTH as usize, height as usize),
+        TextureFormat::Rgba8UnormSrgb,
+    ));
+    let root = spawn_mask(&mut commands, &mask);
+
+    commands.insert_resource(Transition {
+        effect: data.effect,
+        duration: data.duration,
+        elapsed: 0.0,
+        phase: Phase::Cover,
+        covered: std::mem::take(&mut data.covered),
+        root,
+        mask,
+    });
+    commands.remove_resource::<TransitionData>();
+
+    SceneResult::Ok(SceneStage::Update)
+}
+
+fn transition_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transition: ResMut<Transition>,
+    mut images: ResMut<Assets<Image>>,
+) -> SceneResult {
+    transition.elapsed += time.delta_seconds();
+    let done = transition.progress() >= 1.0;
+    match transition.phase {
+        Phase::Cover if done => {
+            for &entity in &transition.covered {
+                commands.entity(entity).despawn_recursive();
+            }
+            commands.entity(transition.root).despawn_recursive();
+            transition.phase = Phase::Covered;
+            transition.elapsed = 0.0;
+        }
+        Phase::Reveal if done => {
+            commands.entity(transition.root).despawn_recursive();
+            images.remove(&transition.mask);
+            commands.remove_resource::<Transition>();
+            return SceneResult::Ok(SceneStage::Update);
+        }
+        _ => {}
+    }
+
+    let progress = transition.cover_progress();
+    if let Some(image) = images.get_mut(&transition.mask) {
+        let size = image.size();
+        image.data = mask_data(
+            transition.effect,
+            progress,
+            size.x as usize,
+            size.y as usize,
+        );
+    }
+    SceneResult::Ok(SceneStage::Update)
+}
+
+/// Runs after the new scene has started, so the mask is drawn over it.
+fn transition_reveal(mut commands: Commands, mut transition: ResMut<Transition>) -> SceneResult {
+    transition.root = spawn_mask(&mut commands, &transition.mask);
+    transition.phase = Phase::Reveal;
+    SceneResult::Ok(SceneStage::Update)
+}
+
+/// Black RGBA pixels with the cover of the effect in the alpha channel.
+fn mask_data(effect: TransitionEffect, progress: f32, width: usize, height: usize) -> Vec<u8> {
+    let aspect = width as f32 / height as f32;
+    let mut data = Vec::with_capacity(width * height * 4);
+    for y in 0..height {
+        for x in 0..width {
+            let cover = effect.cover(
+                progress,
+                (x as f32 + 0.5) / width as f32,
+                (y as f32 + 0.5) / height as f32,
+                aspect,
+            );
+            data.extend_from_slice(&[0, 0, 0, (cover * 255.0) as u8]);
+        }
+    }
+    data
+}
@@ -0,0 +1,293 @@
+use quad::prelude::*;
+
+use crate::{
+    fonts::FontChain,
+    input::{Action, ActionState},
+};
+
+pub const TEXT_SIZE: f32 = 30.0;
+pub const PROMPT_SIZE: f32 = 25.0;
+
+/// Full screen root node with its content centered, despawn it to remove the whole screen.
+pub fn screen(
+    commands: &mut Commands,
+    color: Color,
+    children: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: color.into(),
+            ..Default::default()
+        })
+        .with_children(children)
+        .id()
+}
+
+/// Column layout, children are placed from the bottom up.
+pub fn column(
+    parent: &mut ChildBuilder,
+    align_items: AlignItems,
+    children: impl FnOnce(&mut ChildBuilder),
+) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(children);
+}
+
+pub fn label(
+    parent: &mut ChildBuilder,
+    fonts: &FontChain,
+    text: &str,
+    color: Color,
+    margin: UiRect,
+) {
+    parent.spawn().insert_bundle(UiTextBundle {
+        style: Style {
+            margin,
+            ..Default::default()
+        },
+        text: Text {
+            sections: fonts.sections(text, TEXT_SIZE, color),
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+}
+
+/// Heading placed above the rest of a column.
+pub fn title(parent: &mut ChildBuilder, fonts: &FontChain, text: &str) {
+    let margin = UiRect {
+        bottom: Val::Px(15.0),
+        ..Default::default()
+    };
+    label(parent, fonts, text, Color::YELLOW, margin);
+}
+
+/// Prompt at the bottom of the screen.
+pub fn footer(parent: &mut ChildBuilder, fonts: &FontChain, text: &str, color: Color) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            parent.spawn().insert_bundle(UiTextBundle {
+                text: Text {
+                    sections: fonts.sections(text, PROMPT_SIZE, color),
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+        });
+}
+
+/// Centered lines of text (top to bottom) with a footer prompt.
+pub fn message_screen(
+    commands: &mut Commands,
+    fonts: &FontChain,
+    lines: &[(&str, Color)],
+    prompt: &str,
+) -> Entity {
+    screen(commands, Color::NONE, |parent| {
+        column(parent, AlignItems::Center, |parent| {
+            for (index, (text, color)) in lines.iter().enumerate().rev() {
+                let margin = UiRect {
+                    top: Val::Px(if index > 0 { 15.0 } else { 0.0 }),
+                    ..Default::default()
+                };
+                label(parent, fonts, text, *color, margin);
+            }
+        });
+        footer(parent, fonts, prompt, Color::ORANGE_RED);
+    })
+}
+
+#[derive(Component)]
+pub struct MenuItem {
+    index: usize,
+    /// First text section of the value, the label before it keeps its color.
+    value_start: usize,
+}
+
+/// Selection cursor of a menu list, moved with the up and down actions over the enabled items.
+pub struct MenuList {
+    enabled: Vec<bool>,
+    selected: usize,
+}
+
+impl MenuList {
+    pub fn new(len: usize) -> Self {
+        Self::with_enabled(vec![true; len])
+    }
+
+    pub fn with_enabled(enabled: Vec<bool>) -> Self {
+        let selected = enabled.iter().position(|&enabled| enabled).unwrap_or(0);
+        Self { enabled, selected }
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if self.enabled[index] {
+            self.selected = index;
+        }
+    }
+
+    /// Returns true if the selection has moved.
+    pub fn navigate(&mut self, actions: &ActionState) -> bool {
+        let len = self.enabled.len();
+        let step = if actions.just_pressed(Action::Up) {
+            len - 1
+        } else if actions.just_pressed(Action::Down) {
+            1
+        } else {
+            return false;
+        };
+
+        let mut index = self.selected;
+        for _ in 0..len {
+            index = (index + step) % len;
+            if self.enabled[index] {
+                break;
+            }
+        }
+        let moved = index != self.selected && self.enabled[index];
+        if moved {
+            self.selected = index;
+        }
+        moved
+    }
+
+    fn color(&self, index: usize) -> Color {
+        if !self.enabled[index] {
+            Color::GRAY
+        } else if index == self.selected {
+            Color::YELLOW
+        } else {
+            Color::ORANGE_RED
+        }
+    }
+
+    /// Spawns the items (top to bottom) into a column.
+    pub fn spawn(&self, parent: &mut ChildBuilder, fonts: &FontChain, labels: &[&str]) {
+        for (index, text) in labels.iter().enumerate().rev() {
+            parent
+                .spawn()
+                .insert_bundle(UiTextBundle {
+                    text: Text {
+                        sections: fonts.sections(text, TEXT_SIZE, self.color(index)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(MenuItem {
+                    index,
+                    value_start: 0,
+                });
+        }
+    }
+
+    /// Spawns rows of a label followed by a value (top to bottom) into a column,
+    /// only the value is highlighted.
+    pub fn spawn_rows(
+        &self,
+        parent: &mut ChildBuilder,
+        fonts: &FontChain,
+        rows: &[(&str, String)],
+    ) {
+        for (index, (label, value)) in rows.iter().enumerate().rev() {
+            let label = fonts.sections(&format!("{label}:  "), TEXT_SIZE, Color::GREEN);
+            let value_start = label.len();
+            let value = fonts.sections(value, TEXT_SIZE, self.color(index));
+            parent
+                .spawn()
+                .insert_bundle(UiTextBundle {
+                    text: Text {
+                        sections: [label, value].concat(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(MenuItem { index, value_start });
+        }
+    }
+
+    /// Replaces the values of rows spawned by `spawn_rows`.
+    pub fn set_values(
+        &self,
+        items: &mut Query<(&MenuItem, &mut Text)>,
+        fonts: &FontChain,
+        value: impl Fn(usize) -> String,
+    ) {
+        for (item, mut text) in items.iter_mut() {
+            let sections = fonts.sections(&value(item.index), TEXT_SIZE, self.color(item.index));
+            text.sections.truncate(item.value_start);
+            text.sections.extend(sections);
+        }
+    }
+
+    pub fn highlight(&self, items: &mut Query<(&MenuItem, &mut Text)>) {
+        for (item, mut text) in items.iter_mut() {
+            let color = self.color(item.index);
+            for section in text.sections[item.value_start..].iter_mut() {
+                section.style.color = color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::actions;
+
+    #[test]
+    fn navigation_skips_disabled_entries_and_wraps() {
+        let mut menu = MenuList::with_enabled(vec![true, false, true]);
+        assert_eq!(menu.selected(), 0);
+
+        assert!(menu.navigate(&actions(&[Action::Down])));
+        assert_eq!(menu.selected(), 2);
+        assert!(menu.navigate(&actions(&[Action::Down])));
+        assert_eq!(menu.selected(), 0);
+
+        assert!(menu.navigate(&actions(&[Action::Up])));
+        assert_eq!(menu.selected(), 2);
+        assert!(menu.navigate(&actions(&[Action::Up])));
+        assert_eq!(menu.selected(), 0);
+    }
+
+    #[test]
+    fn navigation_stays_on_the_only_enabled_entry() {
+        let mut menu = MenuList::with_enabled(vec![false, true, false]);
+        assert_eq!(menu.selected(), 1);
+        assert!(!menu.navigate(&actions(&[Action::Down])));
+        assert!(!menu.navigate(&actions(&[Action::Up])));
+        assert_eq!(menu.selected(), 1);
+    }
+}
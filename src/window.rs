@@ -0,0 +1,86 @@
+use quad::prelude::*;
+
+use crate::config::{CONFIG_PATH, Config, DisplayMode};
+
+/// Changes of the window are saved once it has stayed the same for this many seconds.
+const SAVE_DELAY: f32 = 1.0;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+struct Placement {
+    size: (u32, u32),
+    position: Option<(i32, i32)>,
+}
+
+#[derive(Resource)]
+pub struct WindowState {
+    /// Mode restored by the toggle when leaving the windowed mode.
+    fullscreen_mode: DisplayMode,
+    /// Size and position seen on the previous frame.
+    observed: Option<Placement>,
+    save_time: Option<f32>,
+}
+
+impl WindowState {
+    pub fn new(config: &Config) -> Self {
+        let fullscreen_mode = match config.display_mode {
+            DisplayMode::Windowed => DisplayMode::Borderless,
+            mode => mode,
+        };
+        Self {
+            fullscreen_mode,
+            observed: None,
+            save_time: None,
+        }
+    }
+}
+
+pub fn is_alt_pressed(keyboard: &KeyboardInput) -> bool {
+    keyboard.pressed(KeyCode::AltLeft) || keyboard.pressed(KeyCode::AltRight)
+}
+
+/// Toggles fullscreen with Alt+Enter or F11 and remembers the size and position of the window.
+pub fn update_window(
+    time: Res<Time>,
+    keyboard: Res<KeyboardInput>,
+    mut state: ResMut<WindowState>,
+    mut config: ResMut<Config>,
+    mut windows: ResMut<Windows>,
+) {
+    let window = windows.primary_mut();
+    let toggle = keyboard.just_pressed(KeyCode::F11)
+        || (is_alt_pressed(&keyboard) && keyboard.just_pressed(KeyCode::Enter));
+    if toggle {
+        config.display_mode = match config.display_mode {
+            DisplayMode::Windowed => state.fullscreen_mode,
+            mode => {
+                state.fullscreen_mode = mode;
+                DisplayMode::Windowed
+            }
+        };
+        config.apply_window(window);
+        state.save_time = Some(0.0);
+    }
+
+    // The first frame only takes the initial state
+    let size = window.size();
+    let size = (size.x as u32, size.y as u32);
+    let position = window.position().map(|position| (position.x, position.y));
+    let placement = Placement { size, position };
+    let changed = state.observed.is_some_and(|observed| observed != placement);
+    state.observed = Some(placement);
+    if changed && config.display_mode == DisplayMode::Windowed && !toggle {
+        config.window_size = size;
+        config.window_position = position;
+        state.save_time = Some(0.0);
+    }
+
+    if let Some(save_time) = state.save_time.as_mut() {
+        *save_time += time.delta_seconds();
+        if *save_time >= SAVE_DELAY {
+            state.save_time = None;
+            if let Err(error) = config.save(CONFIG_PATH) {
+                log::error!("Unable to save configuration: {error}");
+            }
+        }
+    }
+}